@@ -2,13 +2,77 @@
 #![no_main]
 
 use aya_ebpf::{
-    macros::{kretprobe, map},
-    maps::HashMap,
-    programs::RetProbeContext,
-    helpers::bpf_get_current_pid_tgid,
+    macros::{cgroup_skb, kprobe, kretprobe, map, tracepoint},
+    maps::{Array, HashMap, LruHashMap, RingBuf},
+    programs::{ProbeContext, RetProbeContext, SkBuffContext, TracePointContext},
+    helpers::{
+        bpf_get_current_comm, bpf_get_current_pid_tgid, bpf_ktime_get_ns, bpf_probe_read_kernel, bpf_skb_cgroup_id,
+    },
 };
 use aya_log_ebpf::debug;
 
+/// 协议号，与 IPPROTO_* 一致
+const PROTO_TCP: u32 = 6;
+const PROTO_UDP: u32 = 17;
+
+/// 单次收发的合理上限 (1MB)，超出视为异常值
+const MAX_MSG_BYTES: i64 = 1048576;
+
+const AF_INET: u16 = 2;
+
+// struct sock 开头的 sock_common 中各内核版本一致的字段偏移
+const SKC_DADDR: usize = 0;
+const SKC_DPORT: usize = 12;
+const SKC_NUM: usize = 14;
+const SKC_FAMILY: usize = 16;
+
+// struct sockaddr_in 字段偏移
+const SIN_PORT: usize = 2;
+const SIN_ADDR: usize = 4;
+
+/// 时延直方图的 log2 桶数（微秒），最后一个桶收纳更大的值
+const HISTOGRAM_BUCKETS: usize = 24;
+
+// TRACEPOINT_OFFSETS 的下标，偏移由用户态解析 tracepoint format 后写入，0 表示未知
+const OFFSET_RETRANSMIT_SKADDR: u32 = 0;
+const OFFSET_PROBE_SKADDR: u32 = 1;
+const OFFSET_PROBE_SRTT: u32 = 2;
+const OFFSET_STATE_SKADDR: u32 = 3;
+const OFFSET_STATE_OLDSTATE: u32 = 4;
+const OFFSET_STATE_NEWSTATE: u32 = 5;
+const OFFSET_STATE_PROTOCOL: u32 = 6;
+const OFFSET_RESET_SKADDR: u32 = 7;
+const OFFSET_ISSUE_DEV: u32 = 8;
+const OFFSET_ISSUE_SECTOR: u32 = 9;
+const OFFSET_ISSUE_BYTES: u32 = 10;
+const OFFSET_COMPLETE_DEV: u32 = 11;
+const OFFSET_COMPLETE_SECTOR: u32 = 12;
+const OFFSET_SYS_ENTER_ID: u32 = 13;
+const OFFSET_SYS_EXIT_ID: u32 = 14;
+const OFFSET_SYS_EXIT_RET: u32 = 15;
+const OFFSET_SWITCH_PREV_STATE: u32 = 16;
+const OFFSET_SWITCH_NEXT_PID: u32 = 17;
+const OFFSET_WAKEUP_PID: u32 = 18;
+const OFFSET_FORK_CHILD_PID: u32 = 19;
+
+/// prev_state 中的可报告状态位（TASK_REPORT），均为 0 时表示被抢占、仍可运行
+const TASK_REPORT: u32 = 0x7f;
+
+/// 系统调用返回 [-MAX_ERRNO, -1] 时为错误
+const MAX_ERRNO: i64 = 4095;
+
+// TCP 状态（include/net/tcp_states.h）
+const TCP_ESTABLISHED: u32 = 1;
+const TCP_SYN_SENT: u32 = 2;
+const TCP_CLOSE: u32 = 7;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct NetworkStatsKey {
+    pub tgid: u32,
+    pub protocol: u32,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy)]
 pub struct NetworkStats {
@@ -18,94 +82,773 @@ pub struct NetworkStats {
     pub rx_packets: u64,
 }
 
+/// 按对端划分的流量键（IPv4），地址与远端端口为网络字节序，本地端口为主机字节序
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FlowKey {
+    pub tgid: u32,
+    pub protocol: u32,
+    pub remote_addr: u32,
+    pub remote_port: u16,
+    pub local_port: u16,
+}
+
+/// 入口探针暂存的参数，返回探针中按线程取出
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct MsgArgs {
+    /// struct sock *
+    pub sk: u64,
+    /// struct msghdr *
+    pub msg: u64,
+    /// 同一 sock 上嵌套进入的层数，见 stash_args
+    pub depth: u64,
+}
+
+/// 时延分布（RTT、建连耗时）
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Log2Histogram {
+    /// 第 i 个桶为 [2^i, 2^(i+1)) 微秒
+    pub buckets: [u64; HISTOGRAM_BUCKETS],
+    pub sum_us: u64,
+    pub count: u64,
+}
+
+/// 进程的 TCP 建连统计
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ConnectStats {
+    /// 主动连接次数
+    pub attempts: u64,
+    /// 对端拒绝（收到 RST）
+    pub refused: u64,
+    /// 超时（在定时器等非进程上下文中关闭）
+    pub timeout: u64,
+    /// 其他失败（如进程主动放弃）
+    pub other: u64,
+    /// 被动接受的连接数
+    pub accepted: u64,
+    /// 建连耗时
+    pub latency: Log2Histogram,
+}
+
+/// 进行中的主动连接
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct PendingConnect {
+    pub tgid: u32,
+    /// 收到 RST
+    pub reset: u32,
+    pub start_ns: u64,
+}
+
+/// 块设备请求（tracepoint 中没有 request 指针，以设备与起始扇区标识）
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct IoRequestKey {
+    pub dev: u32,
+    pub _pad: u32,
+    pub sector: u64,
+}
+
+/// 已下发、尚未完成的块设备请求
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct PendingIo {
+    pub tgid: u32,
+    pub bytes: u32,
+    pub issued_ns: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct BlockIoKey {
+    pub tgid: u32,
+    pub dev: u32,
+}
+
+/// 进程在单个块设备上的请求统计
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct BlockIoStats {
+    pub requests: u64,
+    pub bytes: u64,
+    /// 下发到完成的耗时
+    pub latency: Log2Histogram,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SyscallKey {
+    pub tgid: u32,
+    pub nr: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SyscallStats {
+    pub count: u64,
+    pub errors: u64,
+}
+
+/// 线程进入某个调度状态的时刻
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ThreadState {
+    pub tgid: u32,
+    pub _pad: u32,
+    pub since_ns: u64,
+}
+
+/// 进程的调度时延
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SchedStats {
+    /// 阻塞（睡眠）到被唤醒的时间
+    pub off_cpu: Log2Histogram,
+    /// 唤醒或被抢占到重新运行的排队时间
+    pub run_queue: Log2Histogram,
+}
+
+/// 生命周期事件类型
+const EVENT_EXEC: u32 = 1;
+const EVENT_FORK: u32 = 2;
+const EVENT_EXIT: u32 = 3;
+
+/// 进程生命周期事件
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct LifecycleEvent {
+    pub kind: u32,
+    pub pid: u32,
+    /// fork 产生的子任务（可能是线程），其他事件为 0
+    pub child_pid: u32,
+    pub comm: [u8; 16],
+}
+
+/// 流量方向
+#[derive(Clone, Copy)]
+enum Direction {
+    Tx,
+    Rx,
+}
+
+// 每个进程每种协议一个条目
 #[map]
-static NETWORK_STATS: HashMap<u32, NetworkStats> = HashMap::with_max_entries(10240, 0);
+static NETWORK_STATS: HashMap<NetworkStatsKey, NetworkStats> = HashMap::with_max_entries(20480, 0);
 
 #[map]
 static PID_WHITELIST: HashMap<u32, u8> = HashMap::with_max_entries(10240, 0);
 
-#[kretprobe]
-pub fn tcp_sendmsg(ctx: RetProbeContext) -> u32 {
-    match try_tcp_sendmsg(&ctx) {
-        Ok(ret) => ret,
-        Err(_) => 0,
+// 对端数量不可控，使用 LRU 淘汰最久未更新的流
+#[map]
+static FLOW_STATS: LruHashMap<FlowKey, NetworkStats> = LruHashMap::with_max_entries(65536, 0);
+
+// pid_tgid -> 入口参数
+#[map]
+static MSG_ARGS: HashMap<u64, MsgArgs> = HashMap::with_max_entries(10240, 0);
+
+// cgroup id -> 该 cgroup 内套接字收发的 skb 统计
+#[map]
+static CGROUP_STATS: HashMap<u64, NetworkStats> = HashMap::with_max_entries(10240, 0);
+
+// sock 地址 -> 所属进程，在收发入口处记录，供软中断上下文中的 TCP 事件归属
+#[map]
+static SOCK_OWNER: LruHashMap<u64, u32> = LruHashMap::with_max_entries(65536, 0);
+
+#[map]
+static TCP_RETRANSMITS: HashMap<u32, u64> = HashMap::with_max_entries(10240, 0);
+
+#[map]
+static TCP_RTT: HashMap<u32, Log2Histogram> = HashMap::with_max_entries(10240, 0);
+
+#[map]
+static TRACEPOINT_OFFSETS: Array<u32> = Array::with_max_entries(32, 0);
+
+#[map]
+static TCP_CONNECTS: HashMap<u32, ConnectStats> = HashMap::with_max_entries(10240, 0);
+
+// sock 地址 -> 进行中的主动连接
+#[map]
+static PENDING_CONNECTS: LruHashMap<u64, PendingConnect> = LruHashMap::with_max_entries(10240, 0);
+
+#[tracepoint]
+pub fn tcp_retransmit_skb(ctx: TracePointContext) -> u32 {
+    let Some(tgid) = tracepoint_owner(&ctx, OFFSET_RETRANSMIT_SKADDR) else {
+        return 0;
+    };
+
+    let retransmits = unsafe { TCP_RETRANSMITS.get(&tgid).copied().unwrap_or(0) };
+    let _ = TCP_RETRANSMITS.insert(&tgid, &retransmits.saturating_add(1), 0);
+    0
+}
+
+// tcp_rcv_established 中触发，srtt 为平滑 RTT（微秒）
+#[tracepoint]
+pub fn tcp_probe(ctx: TracePointContext) -> u32 {
+    let Some(tgid) = tracepoint_owner(&ctx, OFFSET_PROBE_SKADDR) else {
+        return 0;
+    };
+    let Some(srtt_us) = read_field::<u32>(&ctx, OFFSET_PROBE_SRTT) else {
+        return 0;
+    };
+
+    if unsafe { TCP_RTT.get(&tgid).is_none() } {
+        let empty = Log2Histogram { buckets: [0; HISTOGRAM_BUCKETS], sum_us: 0, count: 0 };
+        let _ = TCP_RTT.insert(&tgid, &empty, 0);
     }
+    let Some(stats) = TCP_RTT.get_ptr_mut(&tgid) else {
+        return 0;
+    };
+
+    unsafe { observe(&mut *stats, srtt_us as u64) };
+    0
 }
 
-fn try_tcp_sendmsg(ctx: &RetProbeContext) -> Result<u32, i64> {
-    let pid_tgid = bpf_get_current_pid_tgid();
-    let tgid = (pid_tgid >> 32) as u32;
-    let tid = (pid_tgid & 0xFFFFFFFF) as u32;
+#[tracepoint]
+pub fn inet_sock_set_state(ctx: TracePointContext) -> u32 {
+    let _ = try_set_state(&ctx);
+    0
+}
 
-    let ret: i64 = ctx.ret().ok_or(0i64)?;
-    
-    // 严格检查：只接受正数且合理范围的值 (最大 1MB)
-    if ret <= 0 || ret > 1048576 {
-        return Ok(0);
+// 连接阶段收到 RST 即为对端拒绝
+#[tracepoint]
+pub fn tcp_receive_reset(ctx: TracePointContext) -> u32 {
+    let Some(sk) = read_field::<u64>(&ctx, OFFSET_RESET_SKADDR) else {
+        return 0;
+    };
+    if let Some(pending) = PENDING_CONNECTS.get_ptr_mut(&sk) {
+        unsafe { (*pending).reset = 1 };
     }
+    0
+}
 
-    let sent_bytes = ret as u64;
+#[map]
+static PENDING_IO: LruHashMap<IoRequestKey, PendingIo> = LruHashMap::with_max_entries(10240, 0);
 
-    let whitelist_value = unsafe {
-        PID_WHITELIST.get(&tgid).copied().unwrap_or(0)
+#[map]
+static BLOCK_IO: HashMap<BlockIoKey, BlockIoStats> = HashMap::with_max_entries(10240, 0);
+
+// 在下发请求的进程上下文中归属；由内核线程回写的脏页不计入任何进程
+#[tracepoint]
+pub fn block_rq_issue(ctx: TracePointContext) -> u32 {
+    let Some(tgid) = current_whitelisted_tgid() else {
+        return 0;
+    };
+    let (Some(dev), Some(sector), Some(bytes)) = (
+        read_field::<u32>(&ctx, OFFSET_ISSUE_DEV),
+        read_field::<u64>(&ctx, OFFSET_ISSUE_SECTOR),
+        read_field::<u32>(&ctx, OFFSET_ISSUE_BYTES),
+    ) else {
+        return 0;
     };
 
-    if whitelist_value == 0 {
-        return Ok(0);
+    let key = IoRequestKey { dev, _pad: 0, sector };
+    let pending = PendingIo { tgid, bytes, issued_ns: unsafe { bpf_ktime_get_ns() } };
+    let _ = PENDING_IO.insert(&key, &pending, 0);
+    0
+}
+
+#[tracepoint]
+pub fn block_rq_complete(ctx: TracePointContext) -> u32 {
+    let (Some(dev), Some(sector)) = (
+        read_field::<u32>(&ctx, OFFSET_COMPLETE_DEV),
+        read_field::<u64>(&ctx, OFFSET_COMPLETE_SECTOR),
+    ) else {
+        return 0;
+    };
+
+    let key = IoRequestKey { dev, _pad: 0, sector };
+    let Some(pending) = (unsafe { PENDING_IO.get(&key).copied() }) else {
+        return 0;
+    };
+    let _ = PENDING_IO.remove(&key);
+
+    let elapsed_us = unsafe { bpf_ktime_get_ns() }.saturating_sub(pending.issued_ns) / 1000;
+    let key = BlockIoKey { tgid: pending.tgid, dev };
+    if unsafe { BLOCK_IO.get(&key).is_none() } {
+        let empty = BlockIoStats {
+            requests: 0,
+            bytes: 0,
+            latency: Log2Histogram { buckets: [0; HISTOGRAM_BUCKETS], sum_us: 0, count: 0 },
+        };
+        let _ = BLOCK_IO.insert(&key, &empty, 0);
+    }
+    if let Some(stats) = BLOCK_IO.get_ptr_mut(&key) {
+        let stats = unsafe { &mut *stats };
+        stats.requests = stats.requests.saturating_add(1);
+        stats.bytes = stats.bytes.saturating_add(pending.bytes as u64);
+        observe(&mut stats.latency, elapsed_us);
     }
+    0
+}
 
-    debug!(ctx, "[TX] TGID={} TID={} sent={} bytes", tgid, tid, sent_bytes);
+// (进程, 系统调用号) -> 调用与错误次数
+#[map]
+static SYSCALLS: HashMap<SyscallKey, SyscallStats> = HashMap::with_max_entries(32768, 0);
 
-    let stats = unsafe {
-        NETWORK_STATS.get(&tgid).copied().unwrap_or(NetworkStats {
-            tx_bytes: 0,
-            rx_bytes: 0,
-            tx_packets: 0,
-            rx_packets: 0,
-        })
+// 每次系统调用都会触发，由用户态按需挂载
+#[tracepoint]
+pub fn sys_enter(ctx: TracePointContext) -> u32 {
+    let Some(tgid) = current_whitelisted_tgid() else {
+        return 0;
+    };
+    let Some(nr) = read_field::<i64>(&ctx, OFFSET_SYS_ENTER_ID).filter(|nr| *nr >= 0) else {
+        return 0;
     };
 
-    let new_stats = NetworkStats {
-        tx_bytes: stats.tx_bytes.saturating_add(sent_bytes),
-        tx_packets: stats.tx_packets.saturating_add(1),
-        rx_bytes: stats.rx_bytes,
-        rx_packets: stats.rx_packets,
+    update_syscall(SyscallKey { tgid, nr: nr as u32 }, |stats| stats.count = stats.count.saturating_add(1));
+    0
+}
+
+#[tracepoint]
+pub fn sys_exit(ctx: TracePointContext) -> u32 {
+    let Some(tgid) = current_whitelisted_tgid() else {
+        return 0;
+    };
+    let (Some(nr), Some(ret)) = (
+        read_field::<i64>(&ctx, OFFSET_SYS_EXIT_ID),
+        read_field::<i64>(&ctx, OFFSET_SYS_EXIT_RET),
+    ) else {
+        return 0;
     };
 
-    let _ = unsafe {
-        NETWORK_STATS.insert(&tgid, &new_stats, 0)
+    if nr >= 0 && (-MAX_ERRNO..0).contains(&ret) {
+        update_syscall(SyscallKey { tgid, nr: nr as u32 }, |stats| stats.errors = stats.errors.saturating_add(1));
+    }
+    0
+}
+
+// 阻塞中的线程（tid -> 进程, 开始阻塞时刻）
+#[map]
+static THREAD_OFF_CPU: LruHashMap<u32, ThreadState> = LruHashMap::with_max_entries(65536, 0);
+
+// 可运行但尚未上 CPU 的线程（tid -> 进程, 进入运行队列时刻）
+#[map]
+static THREAD_RUNNABLE: LruHashMap<u32, ThreadState> = LruHashMap::with_max_entries(65536, 0);
+
+#[map]
+static SCHED_LATENCY: HashMap<u32, SchedStats> = HashMap::with_max_entries(10240, 0);
+
+// 每次上下文切换都会触发，由用户态按需挂载；在 prev 线程的上下文中执行
+#[tracepoint]
+pub fn sched_switch(ctx: TracePointContext) -> u32 {
+    let now = unsafe { bpf_ktime_get_ns() };
+
+    // 换下的线程：被抢占则进入运行队列，否则开始阻塞
+    if let Some(tgid) = current_whitelisted_tgid() {
+        let tid = bpf_get_current_pid_tgid() as u32;
+        let state = ThreadState { tgid, _pad: 0, since_ns: now };
+        match read_field::<u32>(&ctx, OFFSET_SWITCH_PREV_STATE) {
+            Some(prev_state) if prev_state & TASK_REPORT == 0 => {
+                let _ = THREAD_RUNNABLE.insert(&tid, &state, 0);
+            }
+            Some(_) => {
+                let _ = THREAD_OFF_CPU.insert(&tid, &state, 0);
+            }
+            None => {}
+        }
+    }
+
+    // 换上的线程：结束排队
+    let Some(next_tid) = read_field::<i32>(&ctx, OFFSET_SWITCH_NEXT_PID) else {
+        return 0;
     };
+    let next_tid = next_tid as u32;
+    if let Some(state) = unsafe { THREAD_RUNNABLE.get(&next_tid).copied() } {
+        let _ = THREAD_RUNNABLE.remove(&next_tid);
+        update_sched(state.tgid, |stats| observe(&mut stats.run_queue, now.saturating_sub(state.since_ns) / 1000));
+    }
+    0
+}
 
-    Ok(0)
+// 在唤醒者的上下文中执行，只能通过阻塞记录找到被唤醒线程所属进程
+#[tracepoint]
+pub fn sched_wakeup(ctx: TracePointContext) -> u32 {
+    let Some(tid) = read_field::<i32>(&ctx, OFFSET_WAKEUP_PID) else {
+        return 0;
+    };
+    let tid = tid as u32;
+    let Some(state) = (unsafe { THREAD_OFF_CPU.get(&tid).copied() }) else {
+        return 0;
+    };
+    let _ = THREAD_OFF_CPU.remove(&tid);
+
+    // 进程已移出白名单时不再计数
+    if unsafe { PID_WHITELIST.get(&state.tgid).is_none() } {
+        return 0;
+    }
+
+    let now = unsafe { bpf_ktime_get_ns() };
+    update_sched(state.tgid, |stats| observe(&mut stats.off_cpu, now.saturating_sub(state.since_ns) / 1000));
+    let _ = THREAD_RUNNABLE.insert(&tid, &ThreadState { tgid: state.tgid, _pad: 0, since_ns: now }, 0);
+    0
+}
+
+// 生命周期事件，由用户态异步消费
+#[map]
+static PROCESS_EVENTS: RingBuf = RingBuf::with_byte_size(256 * 1024, 0);
+
+// 注册进程的进程名，由用户态同步；重启后的新进程尚未加入白名单，exec 后按进程名命中
+#[map]
+static EXEC_COMMS: HashMap<[u8; 16], u8> = HashMap::with_max_entries(1024, 0);
+
+// 白名单进程 fork 出的子进程，exec 时上报
+#[map]
+static EXEC_CHILDREN: LruHashMap<u32, u8> = LruHashMap::with_max_entries(10240, 0);
+
+// 只上报白名单进程、其子进程以及进程名与注册进程相同的 exec，由用户态再按命令行归属
+#[tracepoint]
+pub fn sched_process_exec(_ctx: TracePointContext) -> u32 {
+    let tgid = (bpf_get_current_pid_tgid() >> 32) as u32;
+
+    let forked = unsafe { EXEC_CHILDREN.get(&tgid).is_some() };
+    if forked {
+        let _ = EXEC_CHILDREN.remove(&tgid);
+    }
+    let watched = forked
+        || unsafe { PID_WHITELIST.get(&tgid).is_some() }
+        || bpf_get_current_comm().is_ok_and(|comm| unsafe { EXEC_COMMS.get(&comm).is_some() });
+    if watched {
+        emit_event(EVENT_EXEC, tgid, 0);
+    }
+    0
+}
+
+#[tracepoint]
+pub fn sched_process_fork(ctx: TracePointContext) -> u32 {
+    let Some(tgid) = current_whitelisted_tgid() else {
+        return 0;
+    };
+    let Some(child_pid) = read_field::<i32>(&ctx, OFFSET_FORK_CHILD_PID) else {
+        return 0;
+    };
+    let _ = EXEC_CHILDREN.insert(&(child_pid as u32), &1, 0);
+    emit_event(EVENT_FORK, tgid, child_pid as u32);
+    0
+}
+
+// 每个线程退出都会触发，只在主线程退出时上报
+#[tracepoint]
+pub fn sched_process_exit(_ctx: TracePointContext) -> u32 {
+    let pid_tgid = bpf_get_current_pid_tgid();
+    if pid_tgid as u32 != (pid_tgid >> 32) as u32 {
+        return 0;
+    }
+    let Some(tgid) = current_whitelisted_tgid() else {
+        return 0;
+    };
+    emit_event(EVENT_EXIT, tgid, 0);
+    0
+}
+
+// 由用户态挂载到进程独占的 cgroup 上，按实际 skb 计数（长度含 IP 与传输层头部）
+#[cgroup_skb]
+pub fn cgroup_skb_ingress(ctx: SkBuffContext) -> i32 {
+    count_skb(&ctx, Direction::Rx)
+}
+
+#[cgroup_skb]
+pub fn cgroup_skb_egress(ctx: SkBuffContext) -> i32 {
+    count_skb(&ctx, Direction::Tx)
+}
+
+// 入口探针：各函数的前两个参数均为 (struct sock *, struct msghdr *)
+#[kprobe]
+pub fn tcp_sendmsg_entry(ctx: ProbeContext) -> u32 {
+    stash_args(&ctx)
+}
+
+#[kprobe]
+pub fn tcp_recvmsg_entry(ctx: ProbeContext) -> u32 {
+    stash_args(&ctx)
+}
+
+#[kprobe]
+pub fn udp_sendmsg_entry(ctx: ProbeContext) -> u32 {
+    stash_args(&ctx)
+}
+
+#[kprobe]
+pub fn udp_recvmsg_entry(ctx: ProbeContext) -> u32 {
+    stash_args(&ctx)
+}
+
+#[kprobe]
+pub fn udpv6_sendmsg_entry(ctx: ProbeContext) -> u32 {
+    stash_args(&ctx)
+}
+
+#[kprobe]
+pub fn udpv6_recvmsg_entry(ctx: ProbeContext) -> u32 {
+    stash_args(&ctx)
+}
+
+#[kretprobe]
+pub fn tcp_sendmsg(ctx: RetProbeContext) -> u32 {
+    record(&ctx, PROTO_TCP, Direction::Tx)
 }
 
 #[kretprobe]
 pub fn tcp_recvmsg(ctx: RetProbeContext) -> u32 {
-    match try_tcp_recvmsg(&ctx) {
+    record(&ctx, PROTO_TCP, Direction::Rx)
+}
+
+#[kretprobe]
+pub fn udp_sendmsg(ctx: RetProbeContext) -> u32 {
+    record(&ctx, PROTO_UDP, Direction::Tx)
+}
+
+#[kretprobe]
+pub fn udp_recvmsg(ctx: RetProbeContext) -> u32 {
+    record(&ctx, PROTO_UDP, Direction::Rx)
+}
+
+// IPv6 的 TCP 同样走 tcp_sendmsg/tcp_recvmsg，UDP 则有独立的入口
+#[kretprobe]
+pub fn udpv6_sendmsg(ctx: RetProbeContext) -> u32 {
+    record(&ctx, PROTO_UDP, Direction::Tx)
+}
+
+#[kretprobe]
+pub fn udpv6_recvmsg(ctx: RetProbeContext) -> u32 {
+    record(&ctx, PROTO_UDP, Direction::Rx)
+}
+
+// 被动建连：accept() 返回新 sock 时处于接受连接的进程上下文，不依赖监听端口，跨网络命名空间也不会误归属
+#[kretprobe]
+pub fn inet_csk_accept(ctx: RetProbeContext) -> u32 {
+    let Some(tgid) = current_whitelisted_tgid() else {
+        return 0;
+    };
+    let Some(sk) = ctx.ret::<u64>().filter(|sk| *sk != 0) else {
+        return 0;
+    };
+
+    update_connects(tgid, |stats| stats.accepted = stats.accepted.saturating_add(1));
+    let _ = SOCK_OWNER.insert(&sk, &tgid, 0);
+    0
+}
+
+fn record(ctx: &RetProbeContext, protocol: u32, direction: Direction) -> u32 {
+    match try_record(ctx, protocol, direction) {
         Ok(ret) => ret,
         Err(_) => 0,
     }
 }
 
-fn try_tcp_recvmsg(ctx: &RetProbeContext) -> Result<u32, i64> {
+fn stash_args(ctx: &ProbeContext) -> u32 {
+    let pid_tgid = bpf_get_current_pid_tgid();
+    let tgid = (pid_tgid >> 32) as u32;
+
+    let whitelisted = unsafe { PID_WHITELIST.get(&tgid).is_some() };
+    if !whitelisted {
+        return 0;
+    }
+
+    let (Some(sk), Some(msg)) = (ctx.arg::<u64>(0), ctx.arg::<u64>(1)) else {
+        return 0;
+    };
+
+    // IPv4 映射地址上的 udpv6_sendmsg 会在内部调用 udp_sendmsg：两层都会触发探针。
+    // 只统计最外层，内层只增加嵌套层数，既不覆盖外层参数也不重复计数。
+    // 同线程上 sock 不同的残留条目（返回探针丢失）直接覆盖
+    if let Some(args) = MSG_ARGS.get_ptr_mut(&pid_tgid) {
+        if unsafe { (*args).sk } == sk {
+            unsafe { (*args).depth += 1 };
+            return 0;
+        }
+    }
+    let _ = MSG_ARGS.insert(&pid_tgid, &MsgArgs { sk, msg, depth: 0 }, 0);
+
+    let owned = unsafe { SOCK_OWNER.get(&sk).is_some_and(|owner| *owner == tgid) };
+    if !owned {
+        let _ = SOCK_OWNER.insert(&sk, &tgid, 0);
+    }
+    0
+}
+
+fn try_set_state(ctx: &TracePointContext) -> Option<()> {
+    // 只跟踪 TCP
+    if read_field::<u16>(ctx, OFFSET_STATE_PROTOCOL)? as u32 != PROTO_TCP {
+        return None;
+    }
+
+    let sk = read_field::<u64>(ctx, OFFSET_STATE_SKADDR)?;
+    let oldstate = read_field::<u32>(ctx, OFFSET_STATE_OLDSTATE)?;
+    let newstate = read_field::<u32>(ctx, OFFSET_STATE_NEWSTATE)?;
+
+    match (oldstate, newstate) {
+        // connect()：进程上下文
+        (TCP_CLOSE, TCP_SYN_SENT) => {
+            let tgid = current_whitelisted_tgid()?;
+            let pending = PendingConnect { tgid, reset: 0, start_ns: unsafe { bpf_ktime_get_ns() } };
+            let _ = PENDING_CONNECTS.insert(&sk, &pending, 0);
+            update_connects(tgid, |stats| stats.attempts = stats.attempts.saturating_add(1));
+        }
+        (TCP_SYN_SENT, TCP_ESTABLISHED) => {
+            let pending = unsafe { PENDING_CONNECTS.get(&sk).copied() }?;
+            let _ = PENDING_CONNECTS.remove(&sk);
+            let elapsed_us = unsafe { bpf_ktime_get_ns() }.saturating_sub(pending.start_ns) / 1000;
+            update_connects(pending.tgid, |stats| observe(&mut stats.latency, elapsed_us));
+            let _ = SOCK_OWNER.insert(&sk, &pending.tgid, 0);
+        }
+        (TCP_SYN_SENT, TCP_CLOSE) => {
+            let pending = unsafe { PENDING_CONNECTS.get(&sk).copied() }?;
+            let _ = PENDING_CONNECTS.remove(&sk);
+            // 超时在重传定时器中关闭；进程上下文中关闭说明是进程自己放弃
+            let in_owner = (bpf_get_current_pid_tgid() >> 32) as u32 == pending.tgid;
+            update_connects(pending.tgid, |stats| {
+                if pending.reset != 0 {
+                    stats.refused = stats.refused.saturating_add(1);
+                } else if in_owner {
+                    stats.other = stats.other.saturating_add(1);
+                } else {
+                    stats.timeout = stats.timeout.saturating_add(1);
+                }
+            });
+        }
+        _ => {}
+    }
+    Some(())
+}
+
+/// 按 TRACEPOINT_OFFSETS 中的偏移读取 tracepoint 字段
+fn read_field<T>(ctx: &TracePointContext, index: u32) -> Option<T> {
+    let offset = tracepoint_offset(index)?;
+    unsafe { ctx.read_at::<T>(offset) }.ok()
+}
+
+/// 当前进程在白名单中时返回其 tgid
+fn current_whitelisted_tgid() -> Option<u32> {
+    let tgid = (bpf_get_current_pid_tgid() >> 32) as u32;
+    let whitelisted = unsafe { PID_WHITELIST.get(&tgid).is_some() };
+    whitelisted.then_some(tgid)
+}
+
+fn update_connects(tgid: u32, update: impl FnOnce(&mut ConnectStats)) {
+    if unsafe { TCP_CONNECTS.get(&tgid).is_none() } {
+        let empty = ConnectStats {
+            attempts: 0,
+            refused: 0,
+            timeout: 0,
+            other: 0,
+            accepted: 0,
+            latency: Log2Histogram { buckets: [0; HISTOGRAM_BUCKETS], sum_us: 0, count: 0 },
+        };
+        let _ = TCP_CONNECTS.insert(&tgid, &empty, 0);
+    }
+    if let Some(stats) = TCP_CONNECTS.get_ptr_mut(&tgid) {
+        update(unsafe { &mut *stats });
+    }
+}
+
+fn update_syscall(key: SyscallKey, update: impl FnOnce(&mut SyscallStats)) {
+    if unsafe { SYSCALLS.get(&key).is_none() } {
+        let _ = SYSCALLS.insert(&key, &SyscallStats { count: 0, errors: 0 }, 0);
+    }
+    if let Some(stats) = SYSCALLS.get_ptr_mut(&key) {
+        update(unsafe { &mut *stats });
+    }
+}
+
+fn update_sched(tgid: u32, update: impl FnOnce(&mut SchedStats)) {
+    if unsafe { SCHED_LATENCY.get(&tgid).is_none() } {
+        let empty = Log2Histogram { buckets: [0; HISTOGRAM_BUCKETS], sum_us: 0, count: 0 };
+        let _ = SCHED_LATENCY.insert(&tgid, &SchedStats { off_cpu: empty, run_queue: empty }, 0);
+    }
+    if let Some(stats) = SCHED_LATENCY.get_ptr_mut(&tgid) {
+        update(unsafe { &mut *stats });
+    }
+}
+
+/// 写入生命周期事件，缓冲区满时丢弃
+fn emit_event(kind: u32, pid: u32, child_pid: u32) {
+    let event = LifecycleEvent {
+        kind,
+        pid,
+        child_pid,
+        comm: bpf_get_current_comm().unwrap_or([0; 16]),
+    };
+    let _ = PROCESS_EVENTS.output(&event, 0);
+}
+
+/// 记录一次时延采样（微秒）
+fn observe(histogram: &mut Log2Histogram, value_us: u64) {
+    let bucket = (log2(value_us.min(u32::MAX as u64) as u32) as usize).min(HISTOGRAM_BUCKETS - 1);
+    histogram.buckets[bucket] = histogram.buckets[bucket].saturating_add(1);
+    histogram.sum_us = histogram.sum_us.saturating_add(value_us);
+    histogram.count = histogram.count.saturating_add(1);
+}
+
+fn tracepoint_offset(index: u32) -> Option<usize> {
+    TRACEPOINT_OFFSETS.get(index).copied().filter(|offset| *offset > 0).map(|offset| offset as usize)
+}
+
+/// tracepoint 中 skaddr 字段对应的白名单进程
+fn tracepoint_owner(ctx: &TracePointContext, index: u32) -> Option<u32> {
+    let sk = read_field::<u64>(ctx, index)?;
+    let tgid = unsafe { SOCK_OWNER.get(&sk).copied() }?;
+
+    // 进程已移出白名单时不再计数（sock 地址可能已被复用）
+    let whitelisted = unsafe { PID_WHITELIST.get(&tgid).is_some() };
+    whitelisted.then_some(tgid)
+}
+
+/// 向下取整的 log2，0 视为 0
+fn log2(mut value: u32) -> u32 {
+    let mut result = 0;
+    if value > 0xFFFF {
+        value >>= 16;
+        result |= 16;
+    }
+    if value > 0xFF {
+        value >>= 8;
+        result |= 8;
+    }
+    if value > 0xF {
+        value >>= 4;
+        result |= 4;
+    }
+    if value > 0x3 {
+        value >>= 2;
+        result |= 2;
+    }
+    result | (value >> 1)
+}
+
+fn try_record(ctx: &RetProbeContext, protocol: u32, direction: Direction) -> Result<u32, i64> {
     let pid_tgid = bpf_get_current_pid_tgid();
     let tgid = (pid_tgid >> 32) as u32;
     let tid = (pid_tgid & 0xFFFFFFFF) as u32;
 
+    // 嵌套的内层调用不计数，由最外层统计（见 stash_args）
+    if let Some(nested) = MSG_ARGS.get_ptr_mut(&pid_tgid) {
+        if unsafe { (*nested).depth } > 0 {
+            unsafe { (*nested).depth -= 1 };
+            return Ok(0);
+        }
+    }
+
+    // 无论本次是否计数都要取出入口参数，避免残留
+    let args = unsafe { MSG_ARGS.get(&pid_tgid).copied() };
+    if args.is_some() {
+        let _ = MSG_ARGS.remove(&pid_tgid);
+    }
+
     let ret: i64 = ctx.ret().ok_or(0i64)?;
-    
-    // 严格检查：只接受正数且小于 1MB 的单次接收
-    if ret <= 0 || ret > 1048576 {
-        if ret < 0 {
-            // 负数是错误码，完全正常，不记录
-        } else if ret > 1048576 {
+
+    // 严格检查：只接受正数且合理范围的值，负数是错误码，完全正常，不记录
+    if ret <= 0 || ret > MAX_MSG_BYTES {
+        if ret > MAX_MSG_BYTES {
             // 异常大的值，记录警告
-            debug!(ctx, "[WARN] Abnormal recv size: TGID={} ret={}", tgid, ret);
+            debug!(ctx, "[WARN] Abnormal msg size: TGID={} proto={} ret={}", tgid, protocol, ret);
         }
         return Ok(0);
     }
 
-    let recv_bytes = ret as u64;
+    let bytes = ret as u64;
 
     let whitelist_value = unsafe {
         PID_WHITELIST.get(&tgid).copied().unwrap_or(0)
@@ -115,10 +858,9 @@ fn try_tcp_recvmsg(ctx: &RetProbeContext) -> Result<u32, i64> {
         return Ok(0);
     }
 
-    debug!(ctx, "[RX] TGID={} TID={} recv={} bytes", tgid, tid, recv_bytes);
-
+    let key = NetworkStatsKey { tgid, protocol };
     let stats = unsafe {
-        NETWORK_STATS.get(&tgid).copied().unwrap_or(NetworkStats {
+        NETWORK_STATS.get(&key).copied().unwrap_or(NetworkStats {
             tx_bytes: 0,
             rx_bytes: 0,
             tx_packets: 0,
@@ -126,20 +868,111 @@ fn try_tcp_recvmsg(ctx: &RetProbeContext) -> Result<u32, i64> {
         })
     };
 
-    let new_stats = NetworkStats {
-        tx_bytes: stats.tx_bytes,
-        tx_packets: stats.tx_packets,
-        rx_bytes: stats.rx_bytes.saturating_add(recv_bytes),
-        rx_packets: stats.rx_packets.saturating_add(1),
+    let new_stats = match direction {
+        Direction::Tx => {
+            debug!(ctx, "[TX] TGID={} TID={} proto={} sent={} bytes", tgid, tid, protocol, bytes);
+            NetworkStats {
+                tx_bytes: stats.tx_bytes.saturating_add(bytes),
+                tx_packets: stats.tx_packets.saturating_add(1),
+                ..stats
+            }
+        }
+        Direction::Rx => {
+            debug!(ctx, "[RX] TGID={} TID={} proto={} recv={} bytes", tgid, tid, protocol, bytes);
+            NetworkStats {
+                rx_bytes: stats.rx_bytes.saturating_add(bytes),
+                rx_packets: stats.rx_packets.saturating_add(1),
+                ..stats
+            }
+        }
     };
 
     let _ = unsafe {
-        NETWORK_STATS.insert(&tgid, &new_stats, 0)
+        NETWORK_STATS.insert(&key, &new_stats, 0)
     };
 
+    if let Some(key) = args.and_then(|args| flow_key(tgid, protocol, &args)) {
+        record_flow(&key, direction, bytes);
+    }
+
     Ok(0)
 }
 
+/// 从 sock 中读出对端；未连接的 UDP 套接字对端为 0，改从 msghdr.msg_name（sockaddr_in）读取
+///
+/// 只处理 IPv4，IPv6 的地址偏移随内核配置变化
+fn flow_key(tgid: u32, protocol: u32, args: &MsgArgs) -> Option<FlowKey> {
+    let sk = args.sk as *const u8;
+    let family: u16 = unsafe { bpf_probe_read_kernel(sk.add(SKC_FAMILY) as *const u16) }.ok()?;
+    if family != AF_INET {
+        return None;
+    }
+
+    let mut remote_addr: u32 = unsafe { bpf_probe_read_kernel(sk.add(SKC_DADDR) as *const u32) }.ok()?;
+    let mut remote_port: u16 = unsafe { bpf_probe_read_kernel(sk.add(SKC_DPORT) as *const u16) }.ok()?;
+    let local_port: u16 = unsafe { bpf_probe_read_kernel(sk.add(SKC_NUM) as *const u16) }.ok()?;
+
+    if remote_addr == 0 && args.msg != 0 {
+        // msg_name 是 struct msghdr 的第一个字段
+        let name: u64 = unsafe { bpf_probe_read_kernel(args.msg as *const u64) }.ok()?;
+        if name != 0 {
+            let name = name as *const u8;
+            let sin_family: u16 = unsafe { bpf_probe_read_kernel(name as *const u16) }.ok()?;
+            if sin_family == AF_INET {
+                remote_port = unsafe { bpf_probe_read_kernel(name.add(SIN_PORT) as *const u16) }.ok()?;
+                remote_addr = unsafe { bpf_probe_read_kernel(name.add(SIN_ADDR) as *const u32) }.ok()?;
+            }
+        }
+    }
+
+    Some(FlowKey {
+        tgid,
+        protocol,
+        remote_addr,
+        remote_port,
+        local_port,
+    })
+}
+
+fn record_flow(key: &FlowKey, direction: Direction, bytes: u64) {
+    let stats = unsafe { FLOW_STATS.get(key).copied() };
+    let _ = FLOW_STATS.insert(key, &accumulate(stats, direction, bytes), 0);
+}
+
+fn count_skb(ctx: &SkBuffContext, direction: Direction) -> i32 {
+    let cgroup_id = unsafe { bpf_skb_cgroup_id(ctx.skb.skb) };
+    let bytes = ctx.len() as u64;
+
+    let stats = unsafe { CGROUP_STATS.get(&cgroup_id).copied() };
+    let _ = CGROUP_STATS.insert(&cgroup_id, &accumulate(stats, direction, bytes), 0);
+
+    // 只统计，始终放行
+    1
+}
+
+/// 在已有统计上累加一次收发
+fn accumulate(stats: Option<NetworkStats>, direction: Direction, bytes: u64) -> NetworkStats {
+    let stats = stats.unwrap_or(NetworkStats {
+        tx_bytes: 0,
+        rx_bytes: 0,
+        tx_packets: 0,
+        rx_packets: 0,
+    });
+
+    match direction {
+        Direction::Tx => NetworkStats {
+            tx_bytes: stats.tx_bytes.saturating_add(bytes),
+            tx_packets: stats.tx_packets.saturating_add(1),
+            ..stats
+        },
+        Direction::Rx => NetworkStats {
+            rx_bytes: stats.rx_bytes.saturating_add(bytes),
+            rx_packets: stats.rx_packets.saturating_add(1),
+            ..stats
+        },
+    }
+}
+
 #[panic_handler]
 fn panic(_info: &core::panic::PanicInfo) -> ! {
     unsafe { core::hint::unreachable_unchecked() }
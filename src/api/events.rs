@@ -0,0 +1,57 @@
+use actix_web::{web, HttpResponse, Responder};
+use futures_util::stream;
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+use crate::models::NamedProcessEvent;
+use crate::state::AppState;
+
+/// 没有事件时发送注释行的间隔，避免代理断开空闲连接
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Deserialize)]
+pub struct EventsQuery {
+    /// 只推送该注册进程的事件
+    pub name: Option<String>,
+}
+
+/// 注册进程的 exec / fork / exit 事件流（Server-Sent Events）
+pub async fn get_events(data: web::Data<AppState>, query: web::Query<EventsQuery>) -> impl Responder {
+    let receiver = data.lock().unwrap().events.subscribe();
+    let name = query.into_inner().name;
+
+    let events = stream::unfold((receiver, name), |(mut receiver, name)| async move {
+        let frame = next_frame(&mut receiver, name.as_deref()).await?;
+        Some((Ok::<_, Infallible>(web::Bytes::from(frame)), (receiver, name)))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(events)
+}
+
+/// 等待下一个要推送的 SSE 帧，事件源关闭时返回 None
+///
+/// 事件已由后台任务归属到注册进程，这里只按名称过滤
+async fn next_frame(receiver: &mut Receiver<NamedProcessEvent>, name: Option<&str>) -> Option<String> {
+    loop {
+        let named = match tokio::time::timeout(KEEPALIVE_INTERVAL, receiver.recv()).await {
+            Err(_) => return Some(": keep-alive\n\n".to_string()),
+            Ok(Ok(named)) => named,
+            Ok(Err(RecvError::Lagged(skipped))) => {
+                log::warn!("Event stream subscriber lagged, {} events dropped", skipped);
+                continue;
+            }
+            Ok(Err(RecvError::Closed)) => return None,
+        };
+        if name.is_some_and(|name| name != named.name) {
+            continue;
+        }
+
+        let data = serde_json::to_string(&named).ok()?;
+        return Some(format!("event: {}\ndata: {}\n\n", named.event.kind.as_str(), data));
+    }
+}
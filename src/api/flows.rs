@@ -0,0 +1,44 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::Deserialize;
+
+use crate::metrics::{collect_filtered, ScrapeFilter};
+use crate::state::AppState;
+
+/// 默认返回的对端数量
+const DEFAULT_LIMIT: usize = 20;
+
+#[derive(Deserialize)]
+pub struct FlowsQuery {
+    /// 返回的对端数量上限
+    pub limit: Option<usize>,
+}
+
+/// 进程按总流量排序的对端列表
+///
+/// 只包含 IPv4 对端：IPv6 流量计入进程的网络总量，但没有按对端的拆分
+pub async fn get_flows(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    query: web::Query<FlowsQuery>,
+) -> impl Responder {
+    let name = path.into_inner();
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+
+    // 只刷新该进程
+    let filter = ScrapeFilter { names: vec![name.clone()], ..ScrapeFilter::default() };
+    let state = collect_filtered(&data, &filter).await;
+
+    let Some(status) = state.processes.get(&name) else {
+        return HttpResponse::NotFound().json(serde_json::json!({
+            "status": "error",
+            "message": format!("Process '{}' not found", name)
+        }));
+    };
+
+    let flows: Vec<_> = status.flows.iter().take(limit).collect();
+    HttpResponse::Ok().json(serde_json::json!({
+        "name": name,
+        "pid": status.pid,
+        "flows": flows
+    }))
+}
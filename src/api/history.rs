@@ -0,0 +1,92 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::Deserialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::services::build_points;
+use crate::state::AppState;
+
+/// 默认查询最近一小时
+const DEFAULT_RANGE_SECS: u64 = 3600;
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    /// 起始时间：Unix 时间戳，或相对时长（如 30m、1h、90s）
+    pub since: Option<String>,
+    /// 降采样步长：秒数或时长（如 60、1m），不传时返回全部采样点
+    pub step: Option<String>,
+}
+
+pub async fn get_history(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    query: web::Query<HistoryQuery>,
+) -> impl Responder {
+    let name = path.into_inner();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let since = match query.since.as_deref() {
+        None => now.saturating_sub(DEFAULT_RANGE_SECS),
+        Some(value) => match value.parse::<u64>() {
+            Ok(timestamp) => timestamp,
+            Err(_) => match parse_duration(value) {
+                Some(secs) => now.saturating_sub(secs),
+                None => return bad_request(format!("Invalid 'since': '{}'", value)),
+            },
+        },
+    };
+
+    let step = match query.step.as_deref() {
+        None => 0,
+        Some(value) => match parse_duration(value) {
+            Some(secs) => secs,
+            None => return bad_request(format!("Invalid 'step': '{}'", value)),
+        },
+    };
+
+    let state = data.lock().unwrap();
+    if !state.processes.contains_key(&name) {
+        return HttpResponse::NotFound().json(serde_json::json!({
+            "status": "error",
+            "message": format!("Process '{}' not found", name)
+        }));
+    }
+
+    let points = state.history
+        .get(&name)
+        .map(|history| build_points(history.since(since), since, step))
+        .unwrap_or_default();
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "name": name,
+        "since": since,
+        "step": step,
+        "points": points
+    }))
+}
+
+fn bad_request(message: String) -> HttpResponse {
+    HttpResponse::BadRequest().json(serde_json::json!({
+        "status": "error",
+        "message": message
+    }))
+}
+
+/// 解析时长：纯数字为秒，支持 s / m / h / d 后缀
+fn parse_duration(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, "s"),
+    };
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return None,
+    };
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
@@ -1,198 +1,66 @@
-use actix_web::{web, HttpResponse, Responder};
-use std::time::{SystemTime, UNIX_EPOCH};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use actix_web::http::header;
 use std::collections::HashMap;
-use sysinfo::System;
 
-use crate::services::{check_process_running, get_process_pid};
 use crate::state::AppState;
-use crate::metrics::METRICS;
-
-pub async fn get_metrics(data: web::Data<AppState>) -> impl Responder {
-    let mut state = data.lock().unwrap();
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-
-    // 克隆 ebpf_loader 避免借用冲突
-    let ebpf_loader = state.ebpf_loader.clone();
-
-    // 先收集需要更新的进程信息
-    let pids_to_update: Vec<(String, Option<i32>, String)> = state.processes.iter()
-        .map(|(name, status)| (name.clone(), status.pid, status.config.cmdline.clone()))
-        .collect();
-    let hostname = System::host_name().unwrap_or_else(|| "unknown".to_string());
-
-    // 用于跟踪每个进程的旧 PID 和新 PID
-    let mut pid_changes: HashMap<String, (Option<i32>, Option<i32>)> = HashMap::new();
-
-
-    // 更新每个进程的状态和统计
-    for (name, old_pid, cmdline) in pids_to_update {
-        // 检查进程状态
-        let is_running = check_process_running(&cmdline);
-        let new_pid = get_process_pid(&cmdline);
-
-        // 记录下 pid 变化
-        pid_changes.insert(name.clone(), (old_pid, new_pid));
-
-        // 当进程监控变更时，更新ebpf白名单保证正常进程监听
-        if old_pid != new_pid {
-            let ebpf_loader_clone = ebpf_loader.clone();
-            drop(state);
-
-            // 移除旧 PID
-            if let Some(old) = old_pid {
-                if let Err(e) = ebpf_loader_clone.remove_pid_from_whitelist(old).await {
-                    log::warn!("Failed to remove old PID {} from eBPF whitelist: {}", old, e);
-                } else {
-                    log::debug!("✓ Removed old PID {} from eBPF whitelist for '{}'", old, name);
-                }
-            }
-
-            // 添加新 PID
-            if let Some(new) = new_pid {
-                if let Err(e) = ebpf_loader_clone.add_pid_to_whitelist(new).await {
-                    log::warn!("Failed to add new PID {} to eBPF whitelist: {}", new, e);
-                } else {
-                    log::debug!("✓ Added new PID {} to eBPF whitelist for '{}'", new, name);
-                }
-            }
-
-            state = data.lock().unwrap();
-        }
-
-        // 收集基础统计（CPU、内存等）- 异步操作
-        let stats = if let Some(p) = new_pid {
-            let ebpf_loader_clone = ebpf_loader.clone();
-            drop(state);  // 释放锁以便执行异步操作
-
-            // 创建临时的 stats_collector
-            let temp_collector = crate::services::StatsCollector::new(ebpf_loader_clone);
-            let collected = temp_collector.collect_stats(p).await;
-
-            state = data.lock().unwrap();  // 重新获取锁
-            collected
-        } else {
-            None
-        };
-
-        // 更新状态
-        if let Some(status) = state.processes.get_mut(&name) {
-            status.is_running = is_running;
-            status.pid = new_pid;
-            status.last_check = now;
-
-            // 更新基础统计
-            if let Some(s) = stats {
-                status.stats = s;
+use crate::metrics::{collect_filtered, counter_origin, openmetrics, CounterOrigin, ScrapeFilter, METRICS};
+
+/// `/metrics?collect[]=cpu&name[]=kafka`：可按采集器与注册名限制单次抓取
+pub async fn get_metrics(req: HttpRequest, data: web::Data<AppState>) -> impl Responder {
+    let filter = match parse_filter(&req) {
+        Ok(filter) => filter,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+
+    // 计时覆盖采集与渲染，在返回响应时（离开作用域）记录
+    let _scrape_timer = METRICS.exporter_scrape_duration.start_timer();
+    let state = collect_filtered(&data, &filter).await;
+
+    // 按 Accept 头协商格式：OpenMetrics 或经典 0.0.4 文本格式
+    let wants_openmetrics = req.headers()
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(openmetrics::accepts_openmetrics);
+
+    if wants_openmetrics {
+        // counter 的 _created 按计数起点取时间（按 name 标签关联）：sysinfo 累计值取进程启动时间，
+        // eBPF 计数取 PID 加入白名单的时间，重启次数跨越多个进程取注册时间，exporter 自身指标取 exporter 启动时间
+        let start_times: HashMap<String, u64> = state.processes.values()
+            .filter(|status| status.is_running && status.stats.start_time > 0)
+            .map(|status| (status.config.name.clone(), status.stats.start_time))
+            .collect();
+        let whitelisted_times: HashMap<String, u64> = state.processes.values()
+            .filter(|status| status.whitelisted_at > 0)
+            .map(|status| (status.config.name.clone(), status.whitelisted_at))
+            .collect();
+        let registered_times: HashMap<String, u64> = state.processes.values()
+            .map(|status| (status.config.name.clone(), status.registered_at))
+            .collect();
+        drop(state);
+
+        let body = METRICS.render_openmetrics(&filter, |family, labels| {
+            if family.starts_with("process_exporter_") {
+                return Some(METRICS.started_at as f64);
             }
-        }
+            let times = match counter_origin(family)? {
+                CounterOrigin::ProcessStart => &start_times,
+                CounterOrigin::Whitelisted => &whitelisted_times,
+                CounterOrigin::Registration => &registered_times,
+            };
+            let name = labels.iter().find(|l| l.get_name() == "name")?;
+            times.get(name.get_value()).map(|t| *t as f64)
+        });
+
+        return HttpResponse::Ok()
+            .content_type(openmetrics::CONTENT_TYPE)
+            .body(body);
     }
 
-    // 使用 Prometheus SDK 更新 metrics
-    for (_, status) in state.processes.iter() {
-        let name = &status.config.name;
-        let cmdline = &status.config.cmdline;
-
-        let labels = &[name.as_str(), cmdline.as_str(), &hostname.clone()];
-
-        // process_pid_info只保留最新的
-        if let Some((old_pid, new_pid)) = pid_changes.get(name) { {
-            if (old_pid != new_pid) {
-                if let Some(old) = old_pid {
-                    let _ = METRICS.process_pid_info
-                        .remove_label_values(&[name.as_str(), &old.to_string(), &hostname.clone()]);
-                    log::info!("Process information changed, removed old PID metric for '{}': {}", name, old);
-                }
-            }
-        }
-            if let Some(new) = new_pid {
-                METRICS.process_pid_info
-                    .with_label_values(&[name.as_str(), &new.to_string(), &hostname.clone()])
-                    .set(1.0);
-            }
-        }
-
-        // process_up
-        METRICS.process_up
-            .with_label_values(labels)
-            .set(if status.is_running { 1.0 } else { 0.0 });
-
-        // 只有进程运行时才输出资源 metrics
-        if status.is_running && status.stats.is_valid() {
-            // CPU
-            METRICS.process_cpu_usage
-                .with_label_values(labels)
-                .set(status.stats.cpu_usage as f64);
-
-            // Memory
-            METRICS.process_memory_bytes
-                .with_label_values(labels)
-                .set(status.stats.memory_bytes as f64);
-
-            METRICS.process_memory_percent
-                .with_label_values(labels)
-                .set(status.stats.memory_percent as f64);
-
-            METRICS.process_virtual_memory_bytes
-                .with_label_values(labels)
-                .set(status.stats.virtual_memory_bytes as f64);
-
-            // Thread count
-            METRICS.process_thread_count
-                .with_label_values(labels)
-                .set(status.stats.thread_count as f64);
-
-            // Disk I/O - 注意：Counter 需要特殊处理
-            // 我们需要重置并设置为当前值
-            let _ = METRICS.process_disk_read_bytes.remove_label_values(labels);
-            METRICS.process_disk_read_bytes
-                .with_label_values(labels)
-                .inc_by(status.stats.disk_read_bytes as f64);
-
-            let _ = METRICS.process_disk_written_bytes.remove_label_values(labels);
-            METRICS.process_disk_written_bytes
-                .with_label_values(labels)
-                .inc_by(status.stats.disk_written_bytes as f64);
-
-            // Network - eBPF 统计
-            let _ = METRICS.process_network_tx_bytes.remove_label_values(labels);
-            METRICS.process_network_tx_bytes
-                .with_label_values(labels)
-                .inc_by(status.stats.network_tx_bytes as f64);
-
-            let _ = METRICS.process_network_rx_bytes.remove_label_values(labels);
-            METRICS.process_network_rx_bytes
-                .with_label_values(labels)
-                .inc_by(status.stats.network_rx_bytes as f64);
-
-            let _ = METRICS.process_network_tx_packets.remove_label_values(labels);
-            METRICS.process_network_tx_packets
-                .with_label_values(labels)
-                .inc_by(status.stats.network_tx_packets as f64);
-
-            let _ = METRICS.process_network_rx_packets.remove_label_values(labels);
-            METRICS.process_network_rx_packets
-                .with_label_values(labels)
-                .inc_by(status.stats.network_rx_packets as f64);
-        }
-
-        // Timestamps
-        METRICS.process_registered_timestamp
-            .with_label_values(labels)
-            .set(status.registered_at as f64);
-
-        METRICS.process_last_check_timestamp
-            .with_label_values(labels)
-            .set(status.last_check as f64);
-    }
-
-    // 释放锁
+    // 进程指标在 gather 时读取注册表，需先释放状态锁
     drop(state);
+    let rendered = METRICS.render(&filter);
 
-    // 渲染 Prometheus metrics
-    match METRICS.render() {
+    match rendered {
         Ok(metrics_text) => HttpResponse::Ok()
             .content_type("text/plain; version=0.0.4")
             .body(metrics_text),
@@ -202,3 +70,15 @@ pub async fn get_metrics(data: web::Data<AppState>) -> impl Responder {
         }
     }
 }
+
+/// 解析抓取参数，拒绝未知或已通过命令行关闭的采集器
+fn parse_filter(req: &HttpRequest) -> Result<ScrapeFilter, String> {
+    let pairs = web::Query::<Vec<(String, String)>>::from_query(req.query_string())
+        .map_err(|e| format!("invalid query string: {}", e))?;
+    let filter = ScrapeFilter::from_query(&pairs)?;
+
+    if let Some(disabled) = filter.collectors.iter().find(|c| !METRICS.is_collector_enabled(c)) {
+        return Err(format!("collector '{}' is disabled", disabled));
+    }
+    Ok(filter)
+}
@@ -1,8 +1,16 @@
 pub mod register;
 pub mod metrics;
+pub mod history;
+pub mod flows;
+pub mod syscalls;
+pub mod events;
 
 pub use register::{register_process, unregister_process, list_processes};
 pub use metrics::get_metrics;
+pub use history::get_history;
+pub use flows::get_flows;
+pub use syscalls::get_syscalls;
+pub use events::get_events;
 
 use actix_web::{HttpResponse, Responder};
 
@@ -3,9 +3,11 @@ use serde::Deserialize;
 use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::models::{ProcessConfig, ProcessStatus, ProcessStats};
-use crate::services::{check_process_running, get_process_pid, get_all_matching_pids};
+use crate::models::{ProbeConfig, ProcessConfig, ProcessStatus, ProcessStats, RestartTracker};
+use crate::services::{check_process_running, get_process_pid, get_all_matching_pids, validate_probes};
+use crate::services::lifecycle::read_comm;
 use crate::state::AppState;
+use crate::metrics::{ScrapeFilter, METRICS};
 
 #[derive(Deserialize)]
 pub struct RegisterRequest {
@@ -13,13 +15,23 @@ pub struct RegisterRequest {
     pub cmdline: String,
     #[serde(default)]
     pub labels: HashMap<String, String>,
+    #[serde(default)]
+    pub ports: Vec<u16>,
+    #[serde(default)]
+    pub probes: Vec<ProbeConfig>,
 }
 
 pub async fn register_process(
     data: web::Data<AppState>,
     req: web::Json<RegisterRequest>,
 ) -> impl Responder {
-    let mut state = data.lock().unwrap();
+    let allow_exec_probes = data.lock().unwrap().allow_exec_probes;
+    if let Err(message) = validate_probes(&req.probes, allow_exec_probes) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": message
+        }));
+    }
 
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -40,33 +52,29 @@ pub async fn register_process(
     log::info!("  All matching PIDs: {:?}", all_pids);
 
     // 检查 PID 是否已被注册
-    if let Some(current_pid) = pid {
-        for (existing_name, existing_status) in state.processes.iter() {
-            if let Some(existing_pid) = existing_status.pid {
-                if existing_pid == current_pid && existing_name != &req.name {
-                    return HttpResponse::Conflict().json(serde_json::json!({
-                        "status": "error",
-                        "message": format!("Process with PID {} is already registered as '{}'", current_pid, existing_name),
-                        "existing_name": existing_name,
-                        "pid": current_pid
-                    }));
+    let (ebpf_loader, stats_collector) = {
+        let state = data.lock().unwrap();
+        if let Some(current_pid) = pid {
+            for (existing_name, existing_status) in state.processes.iter() {
+                if let Some(existing_pid) = existing_status.pid {
+                    if existing_pid == current_pid && existing_name != &req.name {
+                        return HttpResponse::Conflict().json(serde_json::json!({
+                            "status": "error",
+                            "message": format!("Process with PID {} is already registered as '{}'", current_pid, existing_name),
+                            "existing_name": existing_name,
+                            "pid": current_pid
+                        }));
+                    }
                 }
             }
         }
-    }
+        (state.ebpf_loader.clone(), state.stats_collector.clone())
+    };
 
     // 收集进程统计信息
     let stats = if let Some(p) = pid {
-        // 克隆 ebpf_loader，避免借用冲突
-        let ebpf_loader = state.ebpf_loader.clone();
-        drop(state);  // 释放锁以便执行异步操作
-        
-        // 创建临时的 stats_collector
-        let temp_collector = crate::services::StatsCollector::new(ebpf_loader);
-        let collected_stats = temp_collector.collect_stats(p).await.unwrap_or_default();
-        
-        state = data.lock().unwrap();  // 重新获取锁
-        collected_stats
+        let active = METRICS.active_collectors(&ScrapeFilter::default());
+        stats_collector.collect_stats(p, ProcessStats::default(), &active).await.unwrap_or_default()
     } else {
         ProcessStats::empty()
     };
@@ -75,6 +83,8 @@ pub async fn register_process(
         name: req.name.clone(),
         cmdline: req.cmdline.clone(),
         labels: req.labels.clone(),
+        ports: req.ports.clone(),
+        probes: req.probes.clone(),
     };
 
     let status = ProcessStatus {
@@ -83,33 +93,47 @@ pub async fn register_process(
         last_check: now,
         is_running,
         pid,
+        comm: pid.and_then(read_comm).unwrap_or_default(),
         stats: stats.clone(),
+        whitelisted_at: 0,
+        probe_results: HashMap::new(),
+        restarts: RestartTracker::new(pid),
+        flows: Vec::new(),
+        syscalls: Vec::new(),
     };
 
-    let final_status = if let Some(existing) = state.processes.get(&req.name) {
-        ProcessStatus {
-            registered_at: existing.registered_at,
-            ..status
-        }
-    } else {
-        status
-    };
+    {
+        let mut state = data.lock().unwrap();
+        let final_status = if let Some(existing) = state.processes.get(&req.name) {
+            ProcessStatus {
+                registered_at: existing.registered_at,
+                // 同一 PID 重复注册时 eBPF 计数不会清零
+                whitelisted_at: if existing.pid == pid { existing.whitelisted_at } else { 0 },
+                restarts: existing.restarts.clone(),
+                ..status
+            }
+        } else {
+            status
+        };
 
-    state.processes.insert(req.name.clone(), final_status);
+        state.processes.insert(req.name.clone(), final_status);
+    }
 
     // *** 添加到 eBPF 白名单 ***
     if let Some(p) = pid {
         log::info!("  Adding PID {} to eBPF whitelist", p);
-        let ebpf_loader = state.ebpf_loader.clone();
-        drop(state);  // 释放锁
 
         if let Err(e) = ebpf_loader.add_pid_to_whitelist(p).await {
             log::warn!("Failed to add PID {} to eBPF whitelist: {}", p, e);
         } else {
             log::info!("✓ Added PID {} to eBPF monitoring", p);
+            let mut state = data.lock().unwrap();
+            if let Some(status) = state.processes.get_mut(&req.name) {
+                if status.pid == Some(p) && status.whitelisted_at == 0 {
+                    status.whitelisted_at = now;
+                }
+            }
         }
-
-        // 这里不需要重新获取锁，因为后面没有再使用 state
     }
 
     log::info!("=== Registration Complete ===");
@@ -127,16 +151,20 @@ pub async fn unregister_process(
     data: web::Data<AppState>,
     path: web::Path<String>,
 ) -> impl Responder {
-    let mut state = data.lock().unwrap();
     let name = path.into_inner();
+    let (removed, ebpf_loader, stats_collector) = {
+        let mut state = data.lock().unwrap();
+        state.history.remove(&name);
+        (state.processes.remove(&name), state.ebpf_loader.clone(), state.stats_collector.clone())
+    };
 
-    match state.processes.remove(&name) {
+    match removed {
         Some(process_status) => {
+            let _ = METRICS.exporter_collection_errors.remove_label_values(&[name.as_str()]);
+
             // *** 从 eBPF 白名单移除 ***
             if let Some(pid) = process_status.pid {
-                let ebpf_loader = state.ebpf_loader.clone();
-                drop(state);
-
+                stats_collector.forget(pid);
                 if let Err(e) = ebpf_loader.remove_pid_from_whitelist(pid).await {
                     log::warn!("Failed to remove PID {} from eBPF whitelist: {}", pid, e);
                 } else {
@@ -163,11 +191,15 @@ pub async fn list_processes(data: web::Data<AppState>) -> impl Responder {
             "name": p.config.name,
             "cmdline": p.config.cmdline,
             "labels": p.config.labels,
+            "ports": p.config.ports,
+            "probes": p.config.probes,
             "is_running": p.is_running,
             "pid": p.pid,
             "registered_at": p.registered_at,
             "last_check": p.last_check,
-            "stats": p.stats
+            "stats": p.stats,
+            "probe_results": p.probe_results,
+            "restarts": p.restarts
         })
     }).collect();
 
@@ -0,0 +1,55 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::Deserialize;
+
+use crate::metrics::{collect_filtered, ScrapeFilter};
+use crate::services::sort_by_error_rate;
+use crate::state::AppState;
+
+/// 默认返回的系统调用数量
+const DEFAULT_LIMIT: usize = 10;
+
+#[derive(Deserialize)]
+pub struct SyscallsQuery {
+    /// 每个列表返回的系统调用数量上限
+    pub limit: Option<usize>,
+}
+
+/// 进程调用次数最多、错误率最高的系统调用
+pub async fn get_syscalls(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    query: web::Query<SyscallsQuery>,
+) -> impl Responder {
+    let name = path.into_inner();
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+
+    // 只刷新该进程
+    let filter = ScrapeFilter { names: vec![name.clone()], ..ScrapeFilter::default() };
+    let state = collect_filtered(&data, &filter).await;
+
+    let Some(status) = state.processes.get(&name) else {
+        return HttpResponse::NotFound().json(serde_json::json!({
+            "status": "error",
+            "message": format!("Process '{}' not found", name)
+        }));
+    };
+
+    let by_count: Vec<_> = status.syscalls.iter().take(limit).collect();
+    let by_error_rate: Vec<_> = sort_by_error_rate(&status.syscalls)
+        .into_iter()
+        .take(limit)
+        .map(|syscall| serde_json::json!({
+            "nr": syscall.nr,
+            "name": syscall.name,
+            "count": syscall.count,
+            "errors": syscall.errors,
+            "error_rate": syscall.error_rate(),
+        }))
+        .collect();
+    HttpResponse::Ok().json(serde_json::json!({
+        "name": name,
+        "pid": status.pid,
+        "top_by_count": by_count,
+        "top_by_error_rate": by_error_rate
+    }))
+}
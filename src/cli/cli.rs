@@ -1,4 +1,9 @@
 use clap::{{Parser}};
+use std::path::PathBuf;
+
+use crate::metrics::collectors::parse_collector_name;
+use crate::metrics::{CommonLabel, COLLECTOR_NAMES};
+use crate::services::SinkSpec;
 
 /// Process Exporter - 动态进程监控 exporter
 #[derive(Parser, Debug)]
@@ -11,4 +16,183 @@ pub struct CommandArgs {
     /// 监听地址
     #[arg(short = 'a', long, env = "ADDRESS",default_value = "0.0.0.0")]
     pub address: String,
-}
\ No newline at end of file
+
+    /// 健康探测间隔（秒）
+    #[arg(long, env = "PROBE_INTERVAL", default_value_t = 15)]
+    pub probe_interval: u64,
+
+    /// 允许注册 exec 探测：探测命令以 exporter 的身份（通常为 root）在本机执行，注册接口无鉴权，默认关闭
+    #[arg(long, env = "ALLOW_EXEC_PROBES", default_value_t = false)]
+    pub allow_exec_probes: bool,
+
+    /// 抖动判定：窗口内重启次数达到该值即视为抖动
+    #[arg(long, env = "FLAP_THRESHOLD", default_value_t = 3)]
+    pub flap_threshold: usize,
+
+    /// 抖动判定窗口（秒）
+    #[arg(long, env = "FLAP_WINDOW", default_value_t = 300)]
+    pub flap_window: u64,
+
+    /// 对独占 cgroup 的进程挂载 cgroup_skb 程序，按实际 skb 统计网络流量（process_network_skb_*）
+    #[arg(long, env = "CGROUP_NETWORK", default_value_t = false)]
+    pub cgroup_network: bool,
+
+    /// 挂载 raw_syscalls tracepoint 统计被监控进程的系统调用次数与错误（每次系统调用都会触发，有一定开销）
+    #[arg(long, env = "SYSCALL_TRACING", default_value_t = false)]
+    pub syscall_tracing: bool,
+
+    /// 挂载 sched_switch / sched_wakeup tracepoint 统计被监控进程的阻塞时间与运行队列等待时间（每次上下文切换都会触发）
+    #[arg(long, env = "SCHED_TRACING", default_value_t = false)]
+    pub sched_tracing: bool,
+
+    /// 进程指标的通用标签（逗号分隔，可选 cmdline,hostname,user,exe,pid,container_id；name 始终存在）
+    #[arg(long, env = "COMMON_LABELS", value_delimiter = ',', default_value = "hostname")]
+    pub common_labels: Vec<CommonLabel>,
+
+    /// 允许导出的自定义标签键（逗号分隔），导出到 process_labels_info
+    #[arg(long, env = "LABEL_ALLOWLIST", value_delimiter = ',')]
+    pub label_allowlist: Vec<String>,
+
+    /// 同时在每个进程指标上附加自定义标签
+    #[arg(long, env = "LABELS_ON_SERIES", default_value_t = false)]
+    pub labels_on_series: bool,
+
+    /// 启用的采集器（逗号分隔，可选 process,cpu,memory,disk,network,sockets,probe,flows,tcp,syscalls），默认全部启用
+    #[arg(long, env = "COLLECTORS", value_delimiter = ',', value_parser = parse_collector_name)]
+    pub collectors: Vec<String>,
+
+    /// 关闭的采集器（逗号分隔），优先于 --collectors
+    #[arg(long, env = "DISABLED_COLLECTORS", value_delimiter = ',', value_parser = parse_collector_name)]
+    pub disable_collectors: Vec<String>,
+
+    /// 每个进程导出的对端流量序列上限（按总流量取前 N 个），0 表示只通过 API 查看
+    #[arg(long, env = "FLOW_SERIES_LIMIT", default_value_t = 0)]
+    pub flow_series_limit: usize,
+
+    /// 每个进程导出的系统调用序列上限（按调用次数取前 N 个），0 表示只通过 API 查看
+    #[arg(long, env = "SYSCALL_SERIES_LIMIT", default_value_t = 0)]
+    pub syscall_series_limit: usize,
+
+    /// 历史采样间隔（秒），0 表示关闭
+    #[arg(long, env = "HISTORY_INTERVAL", default_value_t = 15)]
+    pub history_interval: u64,
+
+    /// 历史保留时长（秒）
+    #[arg(long, env = "HISTORY_RETENTION", default_value_t = 3600)]
+    pub history_retention: u64,
+
+    /// 推送间隔（秒），用于 remote_write、Pushgateway、OTLP 与各输出 sink
+    #[arg(long, env = "PUSH_INTERVAL", default_value_t = 15)]
+    pub push_interval: u64,
+
+    /// Prometheus remote_write 接收端地址，配置后启用推送，例如 http://prometheus:9090/api/v1/write
+    #[arg(long, env = "REMOTE_WRITE_URL")]
+    pub remote_write_url: Option<String>,
+
+    /// remote_write 单次请求超时（秒）
+    #[arg(long, env = "REMOTE_WRITE_TIMEOUT", default_value_t = 10)]
+    pub remote_write_timeout: u64,
+
+    /// remote_write 可重试失败的最大重试次数
+    #[arg(long, env = "REMOTE_WRITE_MAX_RETRIES", default_value_t = 3)]
+    pub remote_write_max_retries: u32,
+
+    /// remote_write 磁盘缓冲目录，接收端不可用时暂存数据
+    #[arg(long, env = "REMOTE_WRITE_BUFFER_DIR")]
+    pub remote_write_buffer_dir: Option<PathBuf>,
+
+    /// remote_write 磁盘缓冲最多保留的请求数
+    #[arg(long, env = "REMOTE_WRITE_BUFFER_MAX_FILES", default_value_t = 1000)]
+    pub remote_write_buffer_max_files: usize,
+
+    /// remote_write Bearer Token
+    #[arg(long, env = "REMOTE_WRITE_BEARER_TOKEN", hide_env_values = true)]
+    pub remote_write_bearer_token: Option<String>,
+
+    /// remote_write Basic 认证用户名
+    #[arg(long, env = "REMOTE_WRITE_USERNAME")]
+    pub remote_write_username: Option<String>,
+
+    /// remote_write Basic 认证密码
+    #[arg(long, env = "REMOTE_WRITE_PASSWORD", hide_env_values = true)]
+    pub remote_write_password: Option<String>,
+
+    /// Pushgateway 地址，配置后按推送间隔推送，例如 http://pushgateway:9091
+    #[arg(long, env = "PUSHGATEWAY_URL")]
+    pub pushgateway_url: Option<String>,
+
+    /// Pushgateway 分组键中的 job
+    #[arg(long, env = "PUSHGATEWAY_JOB", default_value = "process-exporter")]
+    pub pushgateway_job: String,
+
+    /// 额外的 Pushgateway 分组标签（逗号分隔的 key=value），默认已包含 hostname
+    #[arg(long, env = "PUSHGATEWAY_GROUPING", value_delimiter = ',', value_parser = parse_key_value)]
+    pub pushgateway_grouping: Vec<(String, String)>,
+
+    /// Pushgateway 单次请求超时（秒）
+    #[arg(long, env = "PUSHGATEWAY_TIMEOUT", default_value_t = 10)]
+    pub pushgateway_timeout: u64,
+
+    /// 退出时删除 Pushgateway 分组，而不是推送最后一次
+    #[arg(long, env = "PUSHGATEWAY_DELETE_ON_SHUTDOWN", default_value_t = false)]
+    pub pushgateway_delete_on_shutdown: bool,
+
+    /// OTLP/HTTP Collector 地址，配置后按推送间隔导出，例如 http://otel-collector:4318
+    #[arg(long, env = "OTEL_EXPORTER_OTLP_ENDPOINT")]
+    pub otlp_endpoint: Option<String>,
+
+    /// OTLP 请求头（逗号分隔的 key=value），例如 authorization=Bearer xxx
+    #[arg(long, env = "OTEL_EXPORTER_OTLP_HEADERS", value_delimiter = ',', value_parser = parse_header, hide_env_values = true)]
+    pub otlp_headers: Vec<(String, String)>,
+
+    /// OTLP 单次请求超时（秒）
+    #[arg(long, env = "OTLP_TIMEOUT", default_value_t = 10)]
+    pub otlp_timeout: u64,
+
+    /// 输出 sink（逗号分隔），格式 <influx|graphite>+<tcp|udp|http|https>://<地址>
+    /// 例如 influx+udp://127.0.0.1:8089、graphite+tcp://graphite:2003
+    #[arg(long = "sink", env = "SINKS", value_delimiter = ',')]
+    pub sinks: Vec<SinkSpec>,
+
+    /// Graphite 指标路径前缀
+    #[arg(long, env = "GRAPHITE_PREFIX", default_value = "process")]
+    pub graphite_prefix: String,
+}
+
+impl CommandArgs {
+    /// 最终启用的采集器名称
+    pub fn enabled_collectors(&self) -> Vec<String> {
+        COLLECTOR_NAMES
+            .iter()
+            .filter(|name| self.collectors.is_empty() || self.collectors.iter().any(|c| c == *name))
+            .filter(|name| !self.disable_collectors.iter().any(|c| c == *name))
+            .map(|name| name.to_string())
+            .collect()
+    }
+}
+
+/// 解析 key=value 形式的标签
+fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    let (key, value) = s.split_once('=')
+        .ok_or_else(|| format!("expected key=value, got '{}'", s))?;
+    let key = key.trim();
+
+    let valid = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("invalid label name '{}'", key));
+    }
+    Ok((key.to_string(), value.trim().to_string()))
+}
+
+/// 解析 key=value 形式的 HTTP 请求头
+fn parse_header(s: &str) -> Result<(String, String), String> {
+    let (key, value) = s.split_once('=')
+        .ok_or_else(|| format!("expected key=value, got '{}'", s))?;
+    let key = key.trim();
+
+    if key.is_empty() || !key.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_') {
+        return Err(format!("invalid header name '{}'", key));
+    }
+    Ok((key.to_string(), value.trim().to_string()))
+}
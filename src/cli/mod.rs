@@ -1,2 +1,3 @@
+#[allow(clippy::module_inception)]
 pub mod cli;
-pub use cli::CommandArgs;
\ No newline at end of file
+pub use cli::CommandArgs;
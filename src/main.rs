@@ -1,5 +1,6 @@
 use actix_web::{web, App, HttpServer};
 use clap::Parser;
+use std::time::Duration;
 
 mod models;
 mod services;
@@ -8,9 +9,18 @@ mod api;
 mod cli;
 mod metrics;
 
+use models::FlapPolicy;
 use state::new_state;
-use api::{register_process, unregister_process, list_processes, get_metrics, health};
+use services::lifecycle::spawn_event_attributor;
+use services::{
+    spawn_history_recorder, spawn_prober, spawn_pushgateway, spawn_remote_writer, spawn_sinks,
+    GraphiteSink, InfluxSink, OtlpConfig, OtlpExporter, Pushgateway, PushgatewayConfig,
+    RemoteWriteConfig, RemoteWriter, Sink, SinkFormat,
+};
+use std::sync::Arc;
+use api::{register_process, unregister_process, list_processes, get_history, get_flows, get_syscalls, get_events, get_metrics, health};
 use cli::CommandArgs;
+use metrics::{refresh, spawn_collector, MetricsOptions, ScrapeFilter, METRICS};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -19,7 +29,27 @@ async fn main() -> std::io::Result<()> {
     let args = CommandArgs::parse();
     let bind_address = format!("{}:{}", args.address, args.port);
 
+    metrics::configure(MetricsOptions {
+        common_labels: args.common_labels.clone(),
+        label_allowlist: args.label_allowlist.clone(),
+        labels_on_series: args.labels_on_series,
+        collectors: args.enabled_collectors(),
+        flow_series_limit: args.flow_series_limit,
+        syscall_series_limit: args.syscall_series_limit,
+    });
+
     let state = new_state();
+    METRICS.watch(state.clone());
+
+    {
+        let mut state = state.lock().unwrap();
+        state.flap_policy = FlapPolicy {
+            threshold: args.flap_threshold.max(1),
+            window: args.flap_window.max(1),
+        };
+        state.cgroup_network = args.cgroup_network;
+        state.allow_exec_probes = args.allow_exec_probes;
+    }
 
     // 加载 eBPF
     let ebpf_loader = {
@@ -32,6 +62,19 @@ async fn main() -> std::io::Result<()> {
     match ebpf_loader.load().await {
         Ok(_) => {
             log::info!("✅ eBPF network monitoring loaded successfully");
+            // 采集器关闭时不挂载对应的 tracepoint
+            if args.syscall_tracing && METRICS.is_collector_enabled("syscalls") {
+                match ebpf_loader.enable_syscall_tracing().await {
+                    Ok(()) => log::info!("✅ Syscall tracing enabled"),
+                    Err(e) => log::warn!("⚠️  Failed to enable syscall tracing: {}", e),
+                }
+            }
+            if args.sched_tracing && METRICS.is_collector_enabled("cpu") {
+                match ebpf_loader.enable_sched_tracing().await {
+                    Ok(()) => log::info!("✅ Scheduler latency tracing enabled"),
+                    Err(e) => log::warn!("⚠️  Failed to enable scheduler latency tracing: {}", e),
+                }
+            }
         }
         Err(e) => {
             log::error!("❌ Failed to load eBPF program: {}", e);
@@ -64,20 +107,146 @@ async fn main() -> std::io::Result<()> {
         }
     }
 
+    // 生命周期事件统一归属后广播给 /api/events 订阅者
+    spawn_event_attributor(state.clone());
+
+    // 后台健康探测
+    if METRICS.is_collector_enabled("probe") {
+        spawn_prober(state.clone(), Duration::from_secs(args.probe_interval.max(1)));
+    }
+
+    // 历史采样与各类推送共用的后台采集间隔
+    let mut collect_intervals = Vec::new();
+
+    // 历史采样（环形缓冲区，容量 = 保留时长 / 采样间隔）
+    if let Some(capacity) = args.history_retention.checked_div(args.history_interval) {
+        let capacity = capacity.max(1) as usize;
+        spawn_history_recorder(state.clone(), Duration::from_secs(args.history_interval), capacity);
+        collect_intervals.push(args.history_interval);
+    }
+
+    // Prometheus remote_write 推送
+    if let Some(url) = &args.remote_write_url {
+        let hostname = sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string());
+        let config = RemoteWriteConfig {
+            url: url.clone(),
+            external_labels: vec![
+                ("job".to_string(), "process-exporter".to_string()),
+                ("instance".to_string(), hostname),
+            ],
+            timeout: Duration::from_secs(args.remote_write_timeout.max(1)),
+            max_retries: args.remote_write_max_retries,
+            buffer_dir: args.remote_write_buffer_dir.clone(),
+            buffer_max_files: args.remote_write_buffer_max_files,
+            bearer_token: args.remote_write_bearer_token.clone(),
+            basic_auth: args.remote_write_username.clone()
+                .map(|username| (username, args.remote_write_password.clone())),
+        };
+
+        match RemoteWriter::new(config) {
+            Ok(writer) => {
+                log::info!("📤 Remote write enabled: {}", url);
+                spawn_remote_writer(writer, Duration::from_secs(args.push_interval.max(1)));
+                collect_intervals.push(args.push_interval.max(1));
+            }
+            Err(e) => log::error!("❌ Failed to start remote write: {:#}", e),
+        }
+    }
+
+    // Pushgateway 推送（按 job + hostname 分组）
+    let pushgateway = args.pushgateway_url.as_ref().and_then(|url| {
+        let hostname = sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string());
+        let mut grouping = vec![("hostname".to_string(), hostname)];
+        for (key, value) in &args.pushgateway_grouping {
+            // 显式配置的同名标签覆盖默认值
+            grouping.retain(|(k, _)| k != key);
+            grouping.push((key.clone(), value.clone()));
+        }
+
+        let config = PushgatewayConfig {
+            url: url.clone(),
+            job: args.pushgateway_job.clone(),
+            grouping,
+            timeout: Duration::from_secs(args.pushgateway_timeout.max(1)),
+            delete_on_shutdown: args.pushgateway_delete_on_shutdown,
+        };
+
+        match Pushgateway::new(config) {
+            Ok(pushgateway) => {
+                let pushgateway = Arc::new(pushgateway);
+                log::info!("📤 Pushgateway enabled: {}", pushgateway.group_url());
+                spawn_pushgateway(pushgateway.clone(), Duration::from_secs(args.push_interval.max(1)));
+                collect_intervals.push(args.push_interval.max(1));
+                Some(pushgateway)
+            }
+            Err(e) => {
+                log::error!("❌ Failed to start Pushgateway push: {:#}", e);
+                None
+            }
+        }
+    });
+
+    // 基于进程快照的输出：OTLP、InfluxDB、Graphite
+    let mut sinks: Vec<Box<dyn Sink>> = Vec::new();
+    if let Some(endpoint) = &args.otlp_endpoint {
+        let config = OtlpConfig {
+            endpoint: endpoint.clone(),
+            headers: args.otlp_headers.clone(),
+            timeout: Duration::from_secs(args.otlp_timeout.max(1)),
+        };
+
+        match OtlpExporter::new(config) {
+            Ok(exporter) => sinks.push(Box::new(exporter)),
+            Err(e) => log::error!("❌ Failed to start OTLP export: {:#}", e),
+        }
+    }
+    for spec in &args.sinks {
+        let sink: Box<dyn Sink> = match spec.format {
+            SinkFormat::Influx => Box::new(InfluxSink::new(spec.transport.clone())),
+            SinkFormat::Graphite => Box::new(GraphiteSink::new(spec.transport.clone(), args.graphite_prefix.clone())),
+        };
+        sinks.push(sink);
+    }
+    for sink in &sinks {
+        log::info!("📤 Output sink enabled: {}", sink.describe());
+    }
+    if !sinks.is_empty() {
+        collect_intervals.push(args.push_interval.max(1));
+    }
+    spawn_sinks(state.clone(), sinks, Duration::from_secs(args.push_interval.max(1)));
+
+    // 单一后台采集任务，间隔取各使用方中最短者；历史与推送只读取其刷新后的状态
+    if let Some(interval) = collect_intervals.into_iter().min() {
+        spawn_collector(state.clone(), Duration::from_secs(interval));
+    }
+
     print_banner(&args);
 
-    HttpServer::new(move || {
+    let server_state = state.clone();
+    let result = HttpServer::new(move || {
         App::new()
-            .app_data(web::Data::new(state.clone()))
+            .app_data(web::Data::new(server_state.clone()))
             .route("/api/process/register", web::post().to(register_process))
             .route("/api/process/{name}", web::delete().to(unregister_process))
             .route("/api/process/list", web::get().to(list_processes))
+            .route("/api/process/{name}/history", web::get().to(get_history))
+            .route("/api/process/{name}/flows", web::get().to(get_flows))
+            .route("/api/process/{name}/syscalls", web::get().to(get_syscalls))
+            .route("/api/events", web::get().to(get_events))
             .route("/metrics", web::get().to(get_metrics))
             .route("/health", web::get().to(health))
     })
         .bind(&bind_address)?
         .run()
-        .await
+        .await;
+
+    // 服务退出（SIGINT / SIGTERM）后做最后一次推送或删除分组
+    if let Some(pushgateway) = pushgateway {
+        refresh(&state, &ScrapeFilter::default()).await;
+        pushgateway.shutdown().await;
+    }
+
+    result
 }
 
 fn print_banner(args: &CommandArgs) {
@@ -92,12 +261,46 @@ fn print_banner(args: &CommandArgs) {
     println!("  POST   /api/process/register   - Register a process");
     println!("  DELETE /api/process/{{name}}     - Unregister a process");
     println!("  GET    /api/process/list       - List all processes");
-    println!("  GET    /metrics                - Prometheus metrics");
+    println!("  GET    /api/process/{{name}}/history - Recent stats history");
+    println!("  GET    /api/process/{{name}}/flows   - Top IPv4 network peers (?limit=)");
+    println!("  GET    /api/process/{{name}}/syscalls - Top syscalls by count / error rate (?limit=)");
+    println!("  GET    /api/events             - Process exec / fork / exit stream (SSE, ?name=)");
+    println!("  GET    /metrics                - Prometheus metrics (?collect[]=&name[]=)");
     println!("  GET    /health                 - Health check");
     println!();
     println!("💡 Features:");
     println!("  • CPU, Memory, Disk monitoring (sysinfo)");
-    println!("  • Network traffic monitoring (eBPF)");
+    println!("  • Block I/O latency per device (eBPF tracepoints)");
+    println!("  • Network traffic monitoring (eBPF, TCP / UDP, per peer)");
+    println!("  • TCP retransmits, RTT, connects and accepts (eBPF tracepoints)");
+    println!("  • Process exec / fork / exit events (eBPF ring buffer)");
+    if args.syscall_tracing {
+        println!("  • Syscall counts and errors (eBPF raw_syscalls)");
+    }
+    if args.sched_tracing {
+        println!("  • Off-CPU time and run-queue latency (eBPF sched_switch / sched_wakeup)");
+    }
+    if args.cgroup_network {
+        println!("  • Packet accounting for dedicated cgroups (eBPF cgroup_skb)");
+    }
+    if args.allow_exec_probes {
+        println!("  • Health probes (TCP / HTTP / exec)");
+    } else {
+        println!("  • Health probes (TCP / HTTP)");
+    }
     println!("  • Prometheus metrics export");
+    println!("  • Collectors: {}", args.enabled_collectors().join(", "));
+    if let Some(url) = &args.remote_write_url {
+        println!("  • Prometheus remote_write push → {}", url);
+    }
+    if let Some(url) = &args.pushgateway_url {
+        println!("  • Pushgateway push → {}", url);
+    }
+    if let Some(endpoint) = &args.otlp_endpoint {
+        println!("  • OpenTelemetry OTLP export → {}", endpoint);
+    }
+    for spec in &args.sinks {
+        println!("  • Output sink → {}+{}", spec.format, spec.transport);
+    }
     println!("═══════════════════════════════════════════════════════════");
 }
@@ -0,0 +1,216 @@
+//! 进程状态刷新
+//!
+//! `/metrics` 抓取与各类推送模式共用同一套刷新逻辑，进程指标在 gather 时由注册表快照生成。
+//! 历史采样与推送不各自刷新，而是读取后台采集任务维护的状态。
+
+use std::collections::HashSet;
+use std::sync::MutexGuard;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::metrics::{ScrapeFilter, METRICS};
+use crate::models::{ProcessStats, ProtocolTraffic};
+use crate::services::lifecycle::read_comm;
+use crate::services::{
+    block_io_by_pid, check_process_running, flows_by_pid, get_process_pid, owned_cgroup, syscalls_by_pid,
+    MAX_FLOWS_PER_PROCESS,
+};
+use crate::state::{AppState, AppStateInner};
+
+/// 串行化刷新：并发刷新会在 PID 切换、白名单增删与重启计数上交错
+static REFRESH_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// 按过滤条件刷新注册进程，返回刷新后的状态锁
+pub async fn collect_filtered<'a>(data: &'a AppState, filter: &ScrapeFilter) -> MutexGuard<'a, AppStateInner> {
+    refresh(data, filter).await;
+    data.lock().unwrap()
+}
+
+/// 刷新选中注册进程的 PID、运行状态与资源统计
+pub async fn refresh(data: &AppState, filter: &ScrapeFilter) {
+    let _guard = REFRESH_LOCK.lock().await;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    // 克隆 ebpf_loader / stats_collector，并先收集需要更新的进程信息，避免跨 await 持锁
+    let (ebpf_loader, stats_collector, flap_policy, cgroup_network, pids_to_update) = {
+        let state = data.lock().unwrap();
+        let pids_to_update: Vec<(String, Option<i32>, String, ProcessStats)> = state.processes.iter()
+            .filter(|(name, _)| filter.includes_process(name))
+            .map(|(name, status)| (name.clone(), status.pid, status.config.cmdline.clone(), status.stats.clone()))
+            .collect();
+        METRICS.exporter_registrations.set(state.processes.len() as f64);
+        (
+            state.ebpf_loader.clone(),
+            state.stats_collector.clone(),
+            state.flap_policy,
+            state.cgroup_network,
+            pids_to_update,
+        )
+    };
+
+    // 只运行命令行启用且被本次抓取选中的采集器，未运行的采集器不做对应的采集工作
+    let active = METRICS.active_collectors(filter);
+
+    // 对端流量、块设备 I/O、系统调用按 PID 分组，整张 map 每次刷新只遍历一次
+    let mut flows = if active.contains("flows") {
+        Some(flows_by_pid(ebpf_loader.get_flows().await, MAX_FLOWS_PER_PROCESS))
+    } else {
+        None
+    };
+    let mut block_io = if active.contains("disk") {
+        Some(block_io_by_pid(ebpf_loader.get_block_io().await))
+    } else {
+        None
+    };
+    let mut syscalls = if active.contains("syscalls") {
+        Some(syscalls_by_pid(ebpf_loader.get_syscalls().await))
+    } else {
+        None
+    };
+
+    // 更新每个进程的状态和统计
+    for (name, old_pid, cmdline, previous) in pids_to_update {
+        // 检查进程状态
+        let is_running = check_process_running(&cmdline);
+        let new_pid = get_process_pid(&cmdline);
+
+        // 当进程监控变更时，更新ebpf白名单保证正常进程监听；PID 变化时记录新的白名单时间
+        let mut whitelisted_at = None;
+        if old_pid != new_pid {
+            whitelisted_at = Some(0);
+            // 移除旧 PID
+            if let Some(old) = old_pid {
+                log::info!("Process information changed for '{}': PID {} is gone", name, old);
+                stats_collector.forget(old);
+                if let Err(e) = ebpf_loader.remove_pid_from_whitelist(old).await {
+                    log::warn!("Failed to remove old PID {} from eBPF whitelist: {}", old, e);
+                } else {
+                    log::debug!("✓ Removed old PID {} from eBPF whitelist for '{}'", old, name);
+                }
+            }
+
+            // 添加新 PID
+            if let Some(new) = new_pid {
+                if let Err(e) = ebpf_loader.add_pid_to_whitelist(new).await {
+                    log::warn!("Failed to add new PID {} to eBPF whitelist: {}", new, e);
+                } else {
+                    log::debug!("✓ Added new PID {} to eBPF whitelist for '{}'", new, name);
+                    whitelisted_at = Some(now);
+                }
+            }
+        }
+
+        // 新 PID 的进程名，进程退出后沿用旧值以便识别重启
+        let comm = new_pid.filter(|_| old_pid != new_pid).and_then(read_comm);
+
+        // 收集基础统计（CPU、内存等）- 异步操作；PID 未变时未运行采集器的字段沿用上次统计
+        let mut stats = if let Some(p) = new_pid {
+            let base = if old_pid == new_pid { previous } else { ProcessStats::default() };
+            let collected = stats_collector.collect_stats(p, base, &active).await;
+            if collected.is_none() {
+                log::debug!("Failed to collect stats for '{}' (PID {})", name, p);
+                METRICS.exporter_collection_errors.with_label_values(&[name.as_str()]).inc();
+            }
+            collected
+        } else {
+            None
+        };
+
+        if let (Some(s), Some(p), Some(block_io)) = (stats.as_mut(), new_pid, block_io.as_mut()) {
+            s.block_io = block_io.remove(&(p as u32)).unwrap_or_default();
+        }
+
+        // 进程独占 cgroup 时，挂载 cgroup_skb 统计实际收发的 skb
+        if let (true, Some(s), Some(p)) = (cgroup_network && active.contains("network"), stats.as_mut(), new_pid) {
+            s.network_skb = None;
+            s.cgroup = String::new();
+            if let Some(cgroup) = owned_cgroup(p) {
+                match ebpf_loader.attach_cgroup(&cgroup).await {
+                    Ok(id) => {
+                        s.network_skb = ebpf_loader.get_cgroup_stats(id).await.map(ProtocolTraffic::from);
+                        s.cgroup = cgroup;
+                    }
+                    Err(e) => log::debug!("Failed to attach cgroup_skb for '{}' ({}): {}", name, cgroup, e),
+                }
+            }
+        }
+
+        // 更新状态
+        let mut state = data.lock().unwrap();
+        if let Some(status) = state.processes.get_mut(&name) {
+            status.is_running = is_running;
+            status.pid = new_pid;
+            status.last_check = now;
+            if let Some(whitelisted_at) = whitelisted_at {
+                status.whitelisted_at = whitelisted_at;
+            }
+            if let Some(comm) = comm {
+                status.comm = comm;
+            }
+
+            let was_flapping = status.restarts.flapping;
+            if status.restarts.observe(new_pid, now, &flap_policy) {
+                log::info!("🔁 Process '{}' restarted (PID {:?}), {} restarts so far", name, new_pid, status.restarts.total);
+            }
+            if status.restarts.flapping && !was_flapping {
+                log::warn!(
+                    "⚠️  Process '{}' is flapping: {} restarts in the last {}s",
+                    name, status.restarts.recent_restarts.len(), flap_policy.window
+                );
+            } else if was_flapping && !status.restarts.flapping {
+                log::info!("✓ Process '{}' stopped flapping", name);
+            }
+
+            // 更新基础统计
+            if let Some(s) = stats {
+                status.stats = s;
+            }
+            if let Some(flows) = flows.as_mut() {
+                status.flows = new_pid
+                    .and_then(|p| flows.remove(&(p as u32)))
+                    .unwrap_or_default();
+            }
+            if let Some(syscalls) = syscalls.as_mut() {
+                status.syscalls = new_pid
+                    .and_then(|p| syscalls.remove(&(p as u32)))
+                    .unwrap_or_default();
+            }
+        }
+    }
+
+    // 同步注册进程的进程名，内核据此过滤 exec 事件
+    let comms: HashSet<String> = data.lock().unwrap().processes.values()
+        .map(|status| status.comm.clone())
+        .filter(|comm| !comm.is_empty())
+        .collect();
+    ebpf_loader.set_exec_comms(&comms).await;
+
+    // 卸载已无注册进程使用的 cgroup
+    if cgroup_network {
+        let cgroups: Vec<String> = data.lock().unwrap().processes.values()
+            .map(|status| status.stats.cgroup.clone())
+            .filter(|cgroup| !cgroup.is_empty())
+            .collect();
+        ebpf_loader.retain_cgroups(&cgroups.iter().map(String::as_str).collect::<HashSet<_>>()).await;
+    }
+
+    // eBPF 状态
+    METRICS.exporter_ebpf_loaded.set(if ebpf_loader.is_loaded().await { 1.0 } else { 0.0 });
+    for (map, entries, capacity) in ebpf_loader.map_usage().await {
+        METRICS.exporter_ebpf_map_entries.with_label_values(&[map]).set(entries as f64);
+        METRICS.exporter_ebpf_map_capacity.with_label_values(&[map]).set(capacity as f64);
+    }
+}
+
+/// 启动后台采集任务：按固定间隔刷新全部注册进程，供历史采样与各类推送读取
+pub fn spawn_collector(data: AppState, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            refresh(&data, &ScrapeFilter::default()).await;
+        }
+    });
+}
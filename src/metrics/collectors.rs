@@ -0,0 +1,962 @@
+//! 按名称划分的进程指标采集器
+//!
+//! 每个采集器负责一组相关指标，可通过命令行整体关闭，
+//! 也可在单次抓取中用 `collect[]` 参数只启用其中一部分
+
+use prometheus::proto::{self, LabelPair, Metric, MetricFamily, MetricType};
+use std::collections::BTreeMap;
+
+use crate::models::ProcessStatus;
+use crate::services::ebpf_loader::PROTOCOLS;
+
+/// 所有内置采集器的名称，默认全部启用
+pub const COLLECTOR_NAMES: &[&str] = &["process", "cpu", "memory", "disk", "network", "sockets", "probe", "flows", "tcp", "syscalls"];
+
+/// 所有内置采集器，顺序与 COLLECTOR_NAMES 一致
+const COLLECTORS: &[&dyn Collector] = &[
+    &ProcessCollector,
+    &CpuCollector,
+    &MemoryCollector,
+    &DiskCollector,
+    &NetworkCollector,
+    &SocketCollector,
+    &ProbeCollector,
+    &FlowCollector,
+    &TcpCollector,
+    &SyscallCollector,
+];
+
+/// 进程指标采集器
+pub trait Collector: Send + Sync {
+    /// 采集器名称，用于命令行开关与 `collect[]` 抓取参数
+    fn name(&self) -> &'static str;
+
+    /// 该采集器输出的指标族
+    fn metrics(&self) -> &'static [MetricSpec];
+
+    /// 输出单个进程的样本
+    fn collect(&self, target: &Target, families: &mut Families);
+}
+
+/// 指标族定义
+pub struct MetricSpec {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub kind: MetricType,
+    pub labels: LabelScheme,
+}
+
+/// counter / histogram 的计数起点，用于 OpenMetrics 的 `_created`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterOrigin {
+    /// 进程启动（sysinfo 读取的累计值）
+    ProcessStart,
+    /// 注册（跨越多个进程实例的计数）
+    Registration,
+    /// PID 加入 eBPF 白名单（eBPF map 中的计数从此开始）
+    Whitelisted,
+}
+
+/// 指标族（counter 不含 `_total`）的计数起点，不是进程 counter / histogram 时返回 None
+pub fn counter_origin(family: &str) -> Option<CounterOrigin> {
+    let spec = COLLECTORS
+        .iter()
+        .flat_map(|collector| collector.metrics())
+        .find(|spec| spec.name.strip_suffix("_total").unwrap_or(spec.name) == family)?;
+    if spec.kind != MetricType::COUNTER && spec.kind != MetricType::HISTOGRAM {
+        return None;
+    }
+
+    Some(match spec.name {
+        name if name == PROCESS_DISK_READ_BYTES.name || name == PROCESS_DISK_WRITTEN_BYTES.name => {
+            CounterOrigin::ProcessStart
+        }
+        name if name == PROCESS_RESTARTS.name => CounterOrigin::Registration,
+        // 其余 counter / histogram 均来自 eBPF map
+        _ => CounterOrigin::Whitelisted,
+    })
+}
+
+/// 指标族的标签组成
+pub enum LabelScheme {
+    /// 通用标签之后追加指标特有的标签
+    Common(&'static [&'static str]),
+    /// name、hostname 之后追加指标特有的标签
+    Info(&'static [&'static str]),
+    /// name、hostname 与自定义标签
+    Custom,
+}
+
+/// 单个进程的采集上下文
+pub struct Target<'a> {
+    pub status: &'a ProcessStatus,
+    pub hostname: &'a str,
+    /// 通用标签（name 与配置的通用标签，可能包含自定义标签）
+    pub common_labels: Vec<(&'a str, String)>,
+    /// 自定义标签
+    pub custom_labels: Vec<(&'a str, String)>,
+    /// 每个进程输出的对端流量序列上限
+    pub flow_series_limit: usize,
+    /// 每个进程输出的系统调用序列上限
+    pub syscall_series_limit: usize,
+}
+
+impl Target<'_> {
+    /// 进程正在运行且有有效的资源统计
+    fn has_stats(&self) -> bool {
+        self.status.is_running && self.status.stats.is_valid()
+    }
+
+    /// 按标签组成生成标签对，`extra` 与定义中的标签一一对应
+    fn label_pairs(&self, scheme: &LabelScheme, extra: &[&str]) -> Vec<LabelPair> {
+        let mut pairs: Vec<(&str, &str)> = match scheme {
+            LabelScheme::Common(_) => self.common_labels.iter().map(|(k, v)| (*k, v.as_str())).collect(),
+            LabelScheme::Info(_) | LabelScheme::Custom => {
+                vec![("name", self.status.config.name.as_str()), ("hostname", self.hostname)]
+            }
+        };
+        match scheme {
+            LabelScheme::Common(names) | LabelScheme::Info(names) => pairs.extend(names.iter().copied().zip(extra.iter().copied())),
+            LabelScheme::Custom => pairs.extend(self.custom_labels.iter().map(|(k, v)| (*k, v.as_str()))),
+        }
+
+        // 与 prometheus 的 Vec 类型指标一致，标签按名称排序
+        pairs.sort_by_key(|(name, _)| *name);
+        pairs.into_iter()
+            .map(|(name, value)| {
+                let mut pair = LabelPair::default();
+                pair.set_name(name.to_string());
+                pair.set_value(value.to_string());
+                pair
+            })
+            .collect()
+    }
+}
+
+/// 抓取时按名称累积的指标族
+#[derive(Default)]
+pub struct Families {
+    families: BTreeMap<&'static str, MetricFamily>,
+}
+
+impl Families {
+    /// 追加一个样本
+    pub fn add(&mut self, spec: &MetricSpec, target: &Target, extra: &[&str], value: f64) {
+        let mut metric = Metric::default();
+        metric.set_label(target.label_pairs(&spec.labels, extra).into());
+        match spec.kind {
+            MetricType::COUNTER => {
+                let mut counter = proto::Counter::default();
+                counter.set_value(value);
+                metric.set_counter(counter);
+            }
+            _ => {
+                let mut gauge = proto::Gauge::default();
+                gauge.set_value(value);
+                metric.set_gauge(gauge);
+            }
+        }
+        self.push(spec, metric);
+    }
+
+    /// 追加一个直方图样本，`buckets` 为 (上界, 累计次数)，+Inf 由 count 表示
+    pub fn add_histogram(&mut self, spec: &MetricSpec, target: &Target, extra: &[&str], buckets: &[(f64, u64)], sum: f64, count: u64) {
+        let mut histogram = proto::Histogram::default();
+        histogram.set_sample_count(count);
+        histogram.set_sample_sum(sum);
+        histogram.set_bucket(
+            buckets.iter()
+                .map(|(upper_bound, cumulative_count)| {
+                    let mut bucket = proto::Bucket::default();
+                    bucket.set_upper_bound(*upper_bound);
+                    bucket.set_cumulative_count(*cumulative_count);
+                    bucket
+                })
+                .collect::<Vec<_>>()
+                .into(),
+        );
+
+        let mut metric = Metric::default();
+        metric.set_label(target.label_pairs(&spec.labels, extra).into());
+        metric.set_histogram(histogram);
+        self.push(spec, metric);
+    }
+
+    fn push(&mut self, spec: &MetricSpec, metric: Metric) {
+        self.families
+            .entry(spec.name)
+            .or_insert_with(|| {
+                let mut family = MetricFamily::default();
+                family.set_name(spec.name.to_string());
+                family.set_help(spec.help.to_string());
+                family.set_field_type(spec.kind);
+                family
+            })
+            .mut_metric()
+            .push(metric);
+    }
+
+    pub fn into_vec(self) -> Vec<MetricFamily> {
+        self.families.into_values().collect()
+    }
+}
+
+/// 按名称查找采集器
+pub fn collector_by_name(name: &str) -> Option<&'static dyn Collector> {
+    COLLECTORS.iter().copied().find(|c| c.name() == name)
+}
+
+/// 解析采集器名称（命令行参数）
+pub fn parse_collector_name(s: &str) -> Result<String, String> {
+    let name = s.trim();
+    if COLLECTOR_NAMES.contains(&name) {
+        Ok(name.to_string())
+    } else {
+        Err(format!("unknown collector '{}', expected one of: {}", name, COLLECTOR_NAMES.join(", ")))
+    }
+}
+
+/// 单次抓取的过滤条件，为空表示不限制
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScrapeFilter {
+    /// 只运行这些采集器
+    pub collectors: Vec<String>,
+    /// 只输出这些注册进程
+    pub names: Vec<String>,
+}
+
+impl ScrapeFilter {
+    /// 从查询参数解析，支持 `collect[]` / `collect` 与 `name[]` / `name`
+    pub fn from_query(pairs: &[(String, String)]) -> Result<Self, String> {
+        let mut filter = Self::default();
+        for (key, value) in pairs {
+            match key.as_str() {
+                "collect[]" | "collect" => filter.collectors.push(parse_collector_name(value)?),
+                "name[]" | "name" => filter.names.push(value.clone()),
+                _ => {}
+            }
+        }
+        Ok(filter)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.collectors.is_empty() && self.names.is_empty()
+    }
+
+    pub fn includes_collector(&self, name: &str) -> bool {
+        self.collectors.is_empty() || self.collectors.iter().any(|c| c == name)
+    }
+
+    pub fn includes_process(&self, name: &str) -> bool {
+        self.names.is_empty() || self.names.iter().any(|n| n == name)
+    }
+}
+
+/// 运行状态、元信息与时间戳
+struct ProcessCollector;
+
+const PROCESS_UP: MetricSpec = MetricSpec {
+    name: "process_up",
+    help: "Process is running (1) or down (0)",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_PID_INFO: MetricSpec = MetricSpec {
+    name: "process_pid_info",
+    help: "Process PID information",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Info(&["pid"]),
+};
+const PROCESS_LABELS_INFO: MetricSpec = MetricSpec {
+    name: "process_labels_info",
+    help: "Custom labels of the registered process",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Custom,
+};
+const PROCESS_CMDLINE_INFO: MetricSpec = MetricSpec {
+    name: "process_cmdline_info",
+    help: "Command line pattern of the registered process",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Info(&["cmdline"]),
+};
+const PROCESS_START_TIME: MetricSpec = MetricSpec {
+    name: "process_start_time_seconds",
+    help: "Start time of the matched process since unix epoch in seconds",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_RESTARTS: MetricSpec = MetricSpec {
+    name: "process_restarts_total",
+    help: "Number of times the matched PID changed since registration",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_LAST_RESTART_TIMESTAMP: MetricSpec = MetricSpec {
+    name: "process_last_restart_timestamp_seconds",
+    help: "Unix timestamp of the last detected restart",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_RESTARTS_IN_WINDOW: MetricSpec = MetricSpec {
+    name: "process_restarts_in_window",
+    help: "Number of restarts within the flapping window",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_FLAPPING: MetricSpec = MetricSpec {
+    name: "process_flapping",
+    help: "Process restarted too often within the flapping window (1) or not (0)",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_THREAD_COUNT: MetricSpec = MetricSpec {
+    name: "process_thread_count",
+    help: "Number of threads",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_REGISTERED_TIMESTAMP: MetricSpec = MetricSpec {
+    name: "process_registered_timestamp_seconds",
+    help: "Unix timestamp when process was registered",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_LAST_CHECK_TIMESTAMP: MetricSpec = MetricSpec {
+    name: "process_last_check_timestamp_seconds",
+    help: "Unix timestamp of last process check",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&[]),
+};
+
+impl Collector for ProcessCollector {
+    fn name(&self) -> &'static str {
+        "process"
+    }
+
+    fn metrics(&self) -> &'static [MetricSpec] {
+        &[
+            PROCESS_UP,
+            PROCESS_PID_INFO,
+            PROCESS_LABELS_INFO,
+            PROCESS_CMDLINE_INFO,
+            PROCESS_START_TIME,
+            PROCESS_RESTARTS,
+            PROCESS_LAST_RESTART_TIMESTAMP,
+            PROCESS_RESTARTS_IN_WINDOW,
+            PROCESS_FLAPPING,
+            PROCESS_THREAD_COUNT,
+            PROCESS_REGISTERED_TIMESTAMP,
+            PROCESS_LAST_CHECK_TIMESTAMP,
+        ]
+    }
+
+    fn collect(&self, target: &Target, families: &mut Families) {
+        let status = target.status;
+
+        families.add(&PROCESS_UP, target, &[], if status.is_running { 1.0 } else { 0.0 });
+        families.add(&PROCESS_LABELS_INFO, target, &[], 1.0);
+        families.add(&PROCESS_CMDLINE_INFO, target, &[status.config.cmdline.as_str()], 1.0);
+
+        if let Some(pid) = status.pid {
+            families.add(&PROCESS_PID_INFO, target, &[&pid.to_string()], 1.0);
+        }
+
+        // 重启次数从注册开始计，没有重启过也输出 0
+        families.add(&PROCESS_RESTARTS, target, &[], status.restarts.total as f64);
+        if let Some(last_restart) = status.restarts.last_restart {
+            families.add(&PROCESS_LAST_RESTART_TIMESTAMP, target, &[], last_restart as f64);
+        }
+        families.add(&PROCESS_RESTARTS_IN_WINDOW, target, &[], status.restarts.recent_restarts.len() as f64);
+        families.add(&PROCESS_FLAPPING, target, &[], if status.restarts.flapping { 1.0 } else { 0.0 });
+
+        if status.is_running && status.stats.start_time > 0 {
+            families.add(&PROCESS_START_TIME, target, &[], status.stats.start_time as f64);
+        }
+
+        if target.has_stats() {
+            families.add(&PROCESS_THREAD_COUNT, target, &[], status.stats.thread_count as f64);
+        }
+
+        families.add(&PROCESS_REGISTERED_TIMESTAMP, target, &[], status.registered_at as f64);
+        families.add(&PROCESS_LAST_CHECK_TIMESTAMP, target, &[], status.last_check as f64);
+    }
+}
+
+/// CPU 使用率与调度时延（eBPF，需开启 --sched-tracing）
+struct CpuCollector;
+
+const PROCESS_CPU_USAGE: MetricSpec = MetricSpec {
+    name: "process_cpu_usage_percent",
+    help: "Process CPU usage percentage",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_OFF_CPU: MetricSpec = MetricSpec {
+    name: "process_off_cpu_seconds",
+    help: "Time threads of the process spent blocked before being woken up",
+    kind: MetricType::HISTOGRAM,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_RUN_QUEUE_LATENCY: MetricSpec = MetricSpec {
+    name: "process_run_queue_latency_seconds",
+    help: "Time runnable threads of the process waited for a CPU",
+    kind: MetricType::HISTOGRAM,
+    labels: LabelScheme::Common(&[]),
+};
+
+impl Collector for CpuCollector {
+    fn name(&self) -> &'static str {
+        "cpu"
+    }
+
+    fn metrics(&self) -> &'static [MetricSpec] {
+        &[PROCESS_CPU_USAGE, PROCESS_OFF_CPU, PROCESS_RUN_QUEUE_LATENCY]
+    }
+
+    fn collect(&self, target: &Target, families: &mut Families) {
+        if !target.has_stats() {
+            return;
+        }
+
+        let stats = &target.status.stats;
+        families.add(&PROCESS_CPU_USAGE, target, &[], stats.cpu_usage as f64);
+        for (spec, histogram) in [(&PROCESS_OFF_CPU, &stats.off_cpu), (&PROCESS_RUN_QUEUE_LATENCY, &stats.run_queue_latency)] {
+            if let Some(histogram) = histogram {
+                let sum = histogram.sum_us as f64 / 1e6;
+                families.add_histogram(spec, target, &[], &histogram.cumulative_buckets(), sum, histogram.count);
+            }
+        }
+    }
+}
+
+/// 物理内存与虚拟内存
+struct MemoryCollector;
+
+const PROCESS_MEMORY_BYTES: MetricSpec = MetricSpec {
+    name: "process_memory_bytes",
+    help: "Process memory usage in bytes",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_MEMORY_PERCENT: MetricSpec = MetricSpec {
+    name: "process_memory_percent",
+    help: "Process memory usage percentage",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_VIRTUAL_MEMORY_BYTES: MetricSpec = MetricSpec {
+    name: "process_virtual_memory_bytes",
+    help: "Process virtual memory in bytes",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&[]),
+};
+
+impl Collector for MemoryCollector {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn metrics(&self) -> &'static [MetricSpec] {
+        &[PROCESS_MEMORY_BYTES, PROCESS_MEMORY_PERCENT, PROCESS_VIRTUAL_MEMORY_BYTES]
+    }
+
+    fn collect(&self, target: &Target, families: &mut Families) {
+        if !target.has_stats() {
+            return;
+        }
+
+        let stats = &target.status.stats;
+        families.add(&PROCESS_MEMORY_BYTES, target, &[], stats.memory_bytes as f64);
+        families.add(&PROCESS_MEMORY_PERCENT, target, &[], stats.memory_percent as f64);
+        families.add(&PROCESS_VIRTUAL_MEMORY_BYTES, target, &[], stats.virtual_memory_bytes as f64);
+    }
+}
+
+/// 磁盘 I/O（sysinfo 累计值）与各块设备的请求耗时（eBPF 统计）
+struct DiskCollector;
+
+const PROCESS_DISK_READ_BYTES: MetricSpec = MetricSpec {
+    name: "process_disk_read_bytes",
+    help: "Total disk read bytes",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_DISK_WRITTEN_BYTES: MetricSpec = MetricSpec {
+    name: "process_disk_written_bytes",
+    help: "Total disk written bytes",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&[]),
+};
+
+const PROCESS_BLOCK_IO_REQUESTS: MetricSpec = MetricSpec {
+    name: "process_block_io_requests_total",
+    help: "Block I/O requests issued by the process, by device",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&["device"]),
+};
+const PROCESS_BLOCK_IO_BYTES: MetricSpec = MetricSpec {
+    name: "process_block_io_bytes_total",
+    help: "Bytes of block I/O requests issued by the process, by device",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&["device"]),
+};
+const PROCESS_BLOCK_IO_LATENCY: MetricSpec = MetricSpec {
+    name: "process_block_io_latency_seconds",
+    help: "Time from issue to completion of block I/O requests of the process, by device",
+    kind: MetricType::HISTOGRAM,
+    labels: LabelScheme::Common(&["device"]),
+};
+
+impl Collector for DiskCollector {
+    fn name(&self) -> &'static str {
+        "disk"
+    }
+
+    fn metrics(&self) -> &'static [MetricSpec] {
+        &[
+            PROCESS_DISK_READ_BYTES,
+            PROCESS_DISK_WRITTEN_BYTES,
+            PROCESS_BLOCK_IO_REQUESTS,
+            PROCESS_BLOCK_IO_BYTES,
+            PROCESS_BLOCK_IO_LATENCY,
+        ]
+    }
+
+    fn collect(&self, target: &Target, families: &mut Families) {
+        if !target.has_stats() {
+            return;
+        }
+
+        let stats = &target.status.stats;
+        families.add(&PROCESS_DISK_READ_BYTES, target, &[], stats.disk_read_bytes as f64);
+        families.add(&PROCESS_DISK_WRITTEN_BYTES, target, &[], stats.disk_written_bytes as f64);
+
+        for block in &stats.block_io {
+            let device = [block.device.as_str()];
+            families.add(&PROCESS_BLOCK_IO_REQUESTS, target, &device, block.requests as f64);
+            families.add(&PROCESS_BLOCK_IO_BYTES, target, &device, block.bytes as f64);
+            let latency = &block.latency;
+            let sum = latency.sum_us as f64 / 1e6;
+            families.add_histogram(&PROCESS_BLOCK_IO_LATENCY, target, &device, &latency.cumulative_buckets(), sum, latency.count);
+        }
+    }
+}
+
+/// 网络流量（eBPF 统计，按协议划分；进程独占 cgroup 时另有 cgroup_skb 统计）
+struct NetworkCollector;
+
+const PROCESS_NETWORK_TX_BYTES: MetricSpec = MetricSpec {
+    name: "process_network_tx_bytes",
+    help: "Network transmitted bytes",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&["protocol"]),
+};
+const PROCESS_NETWORK_RX_BYTES: MetricSpec = MetricSpec {
+    name: "process_network_rx_bytes",
+    help: "Network received bytes",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&["protocol"]),
+};
+const PROCESS_NETWORK_TX_PACKETS: MetricSpec = MetricSpec {
+    name: "process_network_tx_packets",
+    help: "Network transmitted packets",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&["protocol"]),
+};
+const PROCESS_NETWORK_RX_PACKETS: MetricSpec = MetricSpec {
+    name: "process_network_rx_packets",
+    help: "Network received packets",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&["protocol"]),
+};
+const PROCESS_NETWORK_SKB_TX_BYTES: MetricSpec = MetricSpec {
+    name: "process_network_skb_tx_bytes",
+    help: "Bytes of packets sent by the process cgroup, including headers",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_NETWORK_SKB_RX_BYTES: MetricSpec = MetricSpec {
+    name: "process_network_skb_rx_bytes",
+    help: "Bytes of packets received by the process cgroup, including headers",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_NETWORK_SKB_TX_PACKETS: MetricSpec = MetricSpec {
+    name: "process_network_skb_tx_packets",
+    help: "Packets sent by the process cgroup",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_NETWORK_SKB_RX_PACKETS: MetricSpec = MetricSpec {
+    name: "process_network_skb_rx_packets",
+    help: "Packets received by the process cgroup",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&[]),
+};
+
+impl Collector for NetworkCollector {
+    fn name(&self) -> &'static str {
+        "network"
+    }
+
+    fn metrics(&self) -> &'static [MetricSpec] {
+        &[
+            PROCESS_NETWORK_TX_BYTES,
+            PROCESS_NETWORK_RX_BYTES,
+            PROCESS_NETWORK_TX_PACKETS,
+            PROCESS_NETWORK_RX_PACKETS,
+            PROCESS_NETWORK_SKB_TX_BYTES,
+            PROCESS_NETWORK_SKB_RX_BYTES,
+            PROCESS_NETWORK_SKB_TX_PACKETS,
+            PROCESS_NETWORK_SKB_RX_PACKETS,
+        ]
+    }
+
+    fn collect(&self, target: &Target, families: &mut Families) {
+        if !target.has_stats() {
+            return;
+        }
+
+        // 每个协议都输出，没有流量时为 0
+        for (_, protocol) in PROTOCOLS {
+            let traffic = target.status.stats.network_by_protocol.get(*protocol).copied().unwrap_or_default();
+            families.add(&PROCESS_NETWORK_TX_BYTES, target, &[protocol], traffic.tx_bytes as f64);
+            families.add(&PROCESS_NETWORK_RX_BYTES, target, &[protocol], traffic.rx_bytes as f64);
+            families.add(&PROCESS_NETWORK_TX_PACKETS, target, &[protocol], traffic.tx_packets as f64);
+            families.add(&PROCESS_NETWORK_RX_PACKETS, target, &[protocol], traffic.rx_packets as f64);
+        }
+
+        if let Some(skb) = target.status.stats.network_skb {
+            families.add(&PROCESS_NETWORK_SKB_TX_BYTES, target, &[], skb.tx_bytes as f64);
+            families.add(&PROCESS_NETWORK_SKB_RX_BYTES, target, &[], skb.rx_bytes as f64);
+            families.add(&PROCESS_NETWORK_SKB_TX_PACKETS, target, &[], skb.tx_packets as f64);
+            families.add(&PROCESS_NETWORK_SKB_RX_PACKETS, target, &[], skb.rx_packets as f64);
+        }
+    }
+}
+
+/// TCP 连接状态、监听端口与期望端口
+struct SocketCollector;
+
+const PROCESS_TCP_CONNECTIONS: MetricSpec = MetricSpec {
+    name: "process_tcp_connections",
+    help: "Number of TCP connections by state; TIME_WAIT counts sockets on listening ports and connections seen open in an earlier collection",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&["state"]),
+};
+const PROCESS_LISTENING_PORT: MetricSpec = MetricSpec {
+    name: "process_listening_port_info",
+    help: "Port the process is listening on",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&["protocol", "port"]),
+};
+const PROCESS_PORT_LISTENING: MetricSpec = MetricSpec {
+    name: "process_port_listening",
+    help: "Expected port is listening (1) or not (0)",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&["port"]),
+};
+
+impl Collector for SocketCollector {
+    fn name(&self) -> &'static str {
+        "sockets"
+    }
+
+    fn metrics(&self) -> &'static [MetricSpec] {
+        &[PROCESS_TCP_CONNECTIONS, PROCESS_LISTENING_PORT, PROCESS_PORT_LISTENING]
+    }
+
+    fn collect(&self, target: &Target, families: &mut Families) {
+        let status = target.status;
+
+        // 期望端口是否在监听（进程未运行时为 0）
+        for port in &status.config.ports {
+            let listening = status.is_running && status.stats.is_listening_on(*port);
+            families.add(&PROCESS_PORT_LISTENING, target, &[&port.to_string()], if listening { 1.0 } else { 0.0 });
+        }
+
+        if !target.has_stats() {
+            return;
+        }
+
+        for (tcp_state, count) in &status.stats.tcp_connections {
+            families.add(&PROCESS_TCP_CONNECTIONS, target, &[tcp_state.as_str()], *count as f64);
+        }
+
+        for listening in &status.stats.listening_ports {
+            families.add(
+                &PROCESS_LISTENING_PORT,
+                target,
+                &[listening.protocol.as_str(), &listening.port.to_string()],
+                1.0,
+            );
+        }
+    }
+}
+
+/// 健康探测结果（由后台任务定期更新）
+struct ProbeCollector;
+
+const PROCESS_PROBE_SUCCESS: MetricSpec = MetricSpec {
+    name: "process_probe_success",
+    help: "Health probe succeeded (1) or failed (0)",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&["probe", "type"]),
+};
+const PROCESS_PROBE_DURATION: MetricSpec = MetricSpec {
+    name: "process_probe_duration_seconds",
+    help: "Duration of the last health probe in seconds",
+    kind: MetricType::GAUGE,
+    labels: LabelScheme::Common(&["probe", "type"]),
+};
+
+impl Collector for ProbeCollector {
+    fn name(&self) -> &'static str {
+        "probe"
+    }
+
+    fn metrics(&self) -> &'static [MetricSpec] {
+        &[PROCESS_PROBE_SUCCESS, PROCESS_PROBE_DURATION]
+    }
+
+    fn collect(&self, target: &Target, families: &mut Families) {
+        for (probe, result) in &target.status.probe_results {
+            let labels = [probe.as_str(), result.probe_type.as_str()];
+            families.add(&PROCESS_PROBE_SUCCESS, target, &labels, if result.success { 1.0 } else { 0.0 });
+            families.add(&PROCESS_PROBE_DURATION, target, &labels, result.duration_seconds);
+        }
+    }
+}
+
+/// 按对端划分的流量（eBPF 统计），每个进程只输出总流量最大的若干个对端
+struct FlowCollector;
+
+const PROCESS_FLOW_TX_BYTES: MetricSpec = MetricSpec {
+    name: "process_flow_tx_bytes",
+    help: "Bytes transmitted to a remote IPv4 peer (IPv6 peers are not tracked)",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&["protocol", "remote_address", "remote_port", "local_port"]),
+};
+const PROCESS_FLOW_RX_BYTES: MetricSpec = MetricSpec {
+    name: "process_flow_rx_bytes",
+    help: "Bytes received from a remote IPv4 peer (IPv6 peers are not tracked)",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&["protocol", "remote_address", "remote_port", "local_port"]),
+};
+
+impl Collector for FlowCollector {
+    fn name(&self) -> &'static str {
+        "flows"
+    }
+
+    fn metrics(&self) -> &'static [MetricSpec] {
+        &[PROCESS_FLOW_TX_BYTES, PROCESS_FLOW_RX_BYTES]
+    }
+
+    fn collect(&self, target: &Target, families: &mut Families) {
+        if !target.status.is_running {
+            return;
+        }
+
+        // flows 已按总流量降序排列
+        for flow in target.status.flows.iter().take(target.flow_series_limit) {
+            let remote_port = flow.remote_port.to_string();
+            let local_port = flow.local_port.to_string();
+            let labels = [flow.protocol.as_str(), flow.remote_address.as_str(), remote_port.as_str(), local_port.as_str()];
+            families.add(&PROCESS_FLOW_TX_BYTES, target, &labels, flow.tx_bytes as f64);
+            families.add(&PROCESS_FLOW_RX_BYTES, target, &labels, flow.rx_bytes as f64);
+        }
+    }
+}
+
+/// TCP 连接质量（eBPF 统计）：重传次数、平滑 RTT 分布与建连统计
+struct TcpCollector;
+
+const PROCESS_TCP_RETRANSMITS: MetricSpec = MetricSpec {
+    name: "process_tcp_retransmits_total",
+    help: "TCP segments retransmitted on sockets of the process",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_TCP_RTT: MetricSpec = MetricSpec {
+    name: "process_tcp_rtt_seconds",
+    help: "Smoothed round-trip time samples of TCP connections of the process",
+    kind: MetricType::HISTOGRAM,
+    labels: LabelScheme::Common(&[]),
+};
+
+const PROCESS_TCP_CONNECT_ATTEMPTS: MetricSpec = MetricSpec {
+    name: "process_tcp_connect_attempts_total",
+    help: "Outbound TCP connection attempts",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_TCP_CONNECT_FAILURES: MetricSpec = MetricSpec {
+    name: "process_tcp_connect_failures_total",
+    help: "Outbound TCP connection attempts that failed, by reason",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&["reason"]),
+};
+const PROCESS_TCP_CONNECT_DURATION: MetricSpec = MetricSpec {
+    name: "process_tcp_connect_duration_seconds",
+    help: "Time to establish outbound TCP connections",
+    kind: MetricType::HISTOGRAM,
+    labels: LabelScheme::Common(&[]),
+};
+const PROCESS_TCP_ACCEPTED: MetricSpec = MetricSpec {
+    name: "process_tcp_accepted_connections_total",
+    help: "Inbound TCP connections accepted by the process",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&[]),
+};
+
+impl Collector for TcpCollector {
+    fn name(&self) -> &'static str {
+        "tcp"
+    }
+
+    fn metrics(&self) -> &'static [MetricSpec] {
+        &[
+            PROCESS_TCP_RETRANSMITS,
+            PROCESS_TCP_RTT,
+            PROCESS_TCP_CONNECT_ATTEMPTS,
+            PROCESS_TCP_CONNECT_FAILURES,
+            PROCESS_TCP_CONNECT_DURATION,
+            PROCESS_TCP_ACCEPTED,
+        ]
+    }
+
+    fn collect(&self, target: &Target, families: &mut Families) {
+        if !target.has_stats() {
+            return;
+        }
+
+        let stats = &target.status.stats;
+        families.add(&PROCESS_TCP_RETRANSMITS, target, &[], stats.tcp_retransmits as f64);
+        if let Some(rtt) = &stats.tcp_rtt {
+            let sum = rtt.sum_us as f64 / 1e6;
+            families.add_histogram(&PROCESS_TCP_RTT, target, &[], &rtt.cumulative_buckets(), sum, rtt.count);
+        }
+
+        let connects = &stats.tcp_connects;
+        families.add(&PROCESS_TCP_CONNECT_ATTEMPTS, target, &[], connects.attempts as f64);
+        for (reason, count) in [("refused", connects.refused), ("timeout", connects.timeout), ("other", connects.other)] {
+            families.add(&PROCESS_TCP_CONNECT_FAILURES, target, &[reason], count as f64);
+        }
+        if let Some(latency) = &connects.latency {
+            let sum = latency.sum_us as f64 / 1e6;
+            families.add_histogram(&PROCESS_TCP_CONNECT_DURATION, target, &[], &latency.cumulative_buckets(), sum, latency.count);
+        }
+        families.add(&PROCESS_TCP_ACCEPTED, target, &[], connects.accepted as f64);
+    }
+}
+
+/// 系统调用次数与错误（eBPF raw_syscalls），每个进程只输出调用次数最多的若干个
+struct SyscallCollector;
+
+const PROCESS_SYSCALLS: MetricSpec = MetricSpec {
+    name: "process_syscalls_total",
+    help: "System calls made by the process",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&["syscall"]),
+};
+const PROCESS_SYSCALL_ERRORS: MetricSpec = MetricSpec {
+    name: "process_syscall_errors_total",
+    help: "System calls of the process that returned an error",
+    kind: MetricType::COUNTER,
+    labels: LabelScheme::Common(&["syscall"]),
+};
+
+impl Collector for SyscallCollector {
+    fn name(&self) -> &'static str {
+        "syscalls"
+    }
+
+    fn metrics(&self) -> &'static [MetricSpec] {
+        &[PROCESS_SYSCALLS, PROCESS_SYSCALL_ERRORS]
+    }
+
+    fn collect(&self, target: &Target, families: &mut Families) {
+        if !target.status.is_running {
+            return;
+        }
+
+        // syscalls 已按调用次数降序排列
+        for syscall in target.status.syscalls.iter().take(target.syscall_series_limit) {
+            let labels = [syscall.name.as_str()];
+            families.add(&PROCESS_SYSCALLS, target, &labels, syscall.count as f64);
+            families.add(&PROCESS_SYSCALL_ERRORS, target, &labels, syscall.errors as f64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn every_name_has_a_collector() {
+        for name in COLLECTOR_NAMES {
+            assert_eq!(collector_by_name(name).map(|c| c.name()), Some(*name));
+        }
+        assert_eq!(COLLECTORS.len(), COLLECTOR_NAMES.len());
+        assert!(collector_by_name("gpu").is_none());
+    }
+
+    #[test]
+    fn counter_origins() {
+        assert_eq!(counter_origin("process_disk_read_bytes"), Some(CounterOrigin::ProcessStart));
+        assert_eq!(counter_origin("process_restarts"), Some(CounterOrigin::Registration));
+        assert_eq!(counter_origin("process_network_tx_bytes"), Some(CounterOrigin::Whitelisted));
+        assert_eq!(counter_origin("process_syscalls"), Some(CounterOrigin::Whitelisted));
+        assert_eq!(counter_origin("process_tcp_rtt_seconds"), Some(CounterOrigin::Whitelisted));
+        assert_eq!(counter_origin("process_memory_bytes"), None);
+        assert_eq!(counter_origin("process_exporter_collection_errors"), None);
+    }
+
+    #[test]
+    fn metric_names_are_unique() {
+        let mut names: Vec<&str> = COLLECTORS.iter()
+            .flat_map(|c| c.metrics().iter().map(|m| m.name))
+            .collect();
+        let total = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), total);
+    }
+
+    #[test]
+    fn parses_repeated_query_params() {
+        let filter = ScrapeFilter::from_query(&pairs(&[
+            ("collect[]", "cpu"),
+            ("collect[]", "network"),
+            ("name[]", "kafka"),
+            ("format", "ignored"),
+        ])).unwrap();
+
+        assert_eq!(filter.collectors, vec!["cpu", "network"]);
+        assert_eq!(filter.names, vec!["kafka"]);
+        assert!(filter.includes_collector("cpu"));
+        assert!(!filter.includes_collector("memory"));
+        assert!(filter.includes_process("kafka"));
+        assert!(!filter.includes_process("redis"));
+    }
+
+    #[test]
+    fn empty_filter_includes_everything() {
+        let filter = ScrapeFilter::from_query(&[]).unwrap();
+        assert!(filter.is_empty());
+        assert!(filter.includes_collector("disk"));
+        assert!(filter.includes_process("anything"));
+    }
+
+    #[test]
+    fn rejects_unknown_collector() {
+        let err = ScrapeFilter::from_query(&pairs(&[("collect[]", "gpu")])).unwrap_err();
+        assert!(err.contains("unknown collector 'gpu'"));
+    }
+}
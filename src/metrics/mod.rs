@@ -1,179 +1,313 @@
+pub mod collect;
+pub mod collectors;
+pub mod openmetrics;
+pub mod process;
+
+pub use collect::{collect_filtered, refresh, spawn_collector};
+pub use collectors::{counter_origin, Collector, CounterOrigin, ScrapeFilter, COLLECTOR_NAMES};
+
+use prometheus::proto::{LabelPair, MetricFamily};
 use prometheus::{
-    Encoder, GaugeVec, CounterVec, Opts, Registry, TextEncoder,
+    Encoder, Gauge, GaugeVec, CounterVec, Histogram, HistogramOpts, Opts, Registry, TextEncoder,
     register_gauge_vec_with_registry, register_counter_vec_with_registry,
+    register_gauge_with_registry, register_histogram_with_registry,
 };
 use lazy_static::lazy_static;
-use std::sync::Arc;
-use sysinfo::System;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::models::ProcessStatus;
+use crate::state::AppState;
+use process::{LabelLayout, ProcessMetrics};
+
+/// 内置标签名，自定义标签不能与之重名（le / quantile 为 histogram / summary 保留）
+pub(crate) const RESERVED_LABELS: &[&str] = &[
+    "name", "cmdline", "hostname", "user", "exe", "pid", "container_id",
+    "state", "protocol", "port", "probe", "type",
+    "remote_address", "remote_port", "local_port", "reason", "device", "syscall",
+    "le", "quantile",
+];
+
+/// 可附加到每个进程指标上的通用标签（name 始终存在）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommonLabel {
+    Cmdline,
+    Hostname,
+    User,
+    Exe,
+    Pid,
+    ContainerId,
+}
+
+impl CommonLabel {
+    /// 标签名
+    pub fn label_name(&self) -> &'static str {
+        match self {
+            CommonLabel::Cmdline => "cmdline",
+            CommonLabel::Hostname => "hostname",
+            CommonLabel::User => "user",
+            CommonLabel::Exe => "exe",
+            CommonLabel::Pid => "pid",
+            CommonLabel::ContainerId => "container_id",
+        }
+    }
+
+    /// 从进程状态中取出标签值
+    fn value(&self, status: &ProcessStatus, hostname: &str) -> String {
+        match self {
+            CommonLabel::Cmdline => status.config.cmdline.clone(),
+            CommonLabel::Hostname => hostname.to_string(),
+            CommonLabel::User => status.stats.user.clone(),
+            CommonLabel::Exe => status.stats.exe.clone(),
+            CommonLabel::Pid => status.pid.map(|p| p.to_string()).unwrap_or_default(),
+            CommonLabel::ContainerId => status.stats.container_id.clone(),
+        }
+    }
+}
+
+impl FromStr for CommonLabel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "cmdline" => Ok(CommonLabel::Cmdline),
+            "hostname" => Ok(CommonLabel::Hostname),
+            "user" => Ok(CommonLabel::User),
+            "exe" => Ok(CommonLabel::Exe),
+            "pid" => Ok(CommonLabel::Pid),
+            "container_id" => Ok(CommonLabel::ContainerId),
+            other => Err(format!(
+                "unknown label '{}', expected one of: cmdline, hostname, user, exe, pid, container_id",
+                other
+            )),
+        }
+    }
+}
+
+/// Metrics 配置（需在首次访问 METRICS 之前通过 configure 设置）
+#[derive(Debug, Clone)]
+pub struct MetricsOptions {
+    /// 进程指标的通用标签（name 之外）
+    pub common_labels: Vec<CommonLabel>,
+    /// 允许导出的自定义标签键
+    pub label_allowlist: Vec<String>,
+    /// 是否在每个进程指标上附加自定义标签
+    pub labels_on_series: bool,
+    /// 启用的采集器名称
+    pub collectors: Vec<String>,
+    /// 每个进程输出的对端流量序列上限，0 表示不输出
+    pub flow_series_limit: usize,
+    /// 每个进程输出的系统调用序列上限，0 表示不输出
+    pub syscall_series_limit: usize,
+}
+
+impl Default for MetricsOptions {
+    fn default() -> Self {
+        Self {
+            common_labels: vec![CommonLabel::Hostname],
+            label_allowlist: Vec::new(),
+            labels_on_series: false,
+            collectors: COLLECTOR_NAMES.iter().map(|c| c.to_string()).collect(),
+            flow_series_limit: 0,
+            syscall_series_limit: 0,
+        }
+    }
+}
+
+static OPTIONS: OnceLock<MetricsOptions> = OnceLock::new();
+
+/// 设置 Metrics 配置，仅首次调用生效
+pub fn configure(options: MetricsOptions) {
+    if OPTIONS.set(options).is_err() {
+        log::warn!("Metrics options already configured, ignoring");
+    }
+}
+
+/// 将任意字符串转换为合法的 Prometheus 标签名
+pub(crate) fn sanitize_label_name(key: &str) -> String {
+    let mut label: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if label.starts_with(|c: char| c.is_ascii_digit()) {
+        label.insert(0, '_');
+    }
+    label
+}
 
 pub struct MetricsRegistry {
     registry: Registry,
 
-    // Gauge metrics
-    pub process_up: GaugeVec,
-    pub process_pid_info: GaugeVec,
-    pub process_cpu_usage: GaugeVec,
-    pub process_memory_bytes: GaugeVec,
-    pub process_memory_percent: GaugeVec,
-    pub process_virtual_memory_bytes: GaugeVec,
-    pub process_thread_count: GaugeVec,
-    pub process_registered_timestamp: GaugeVec,
-    pub process_last_check_timestamp: GaugeVec,
-
-    // Counter metrics
-    pub process_disk_read_bytes: CounterVec,
-    pub process_disk_written_bytes: CounterVec,
-    pub process_network_tx_bytes: CounterVec,
-    pub process_network_rx_bytes: CounterVec,
-    pub process_network_tx_packets: CounterVec,
-    pub process_network_rx_packets: CounterVec,
+    /// 进程指标的标签组成
+    options: MetricsOptions,
+    /// 启用的采集器
+    collectors: Vec<&'static dyn Collector>,
+
+    // Exporter self metrics
+    pub exporter_scrape_duration: Histogram,
+    pub exporter_collection_errors: CounterVec,
+    pub exporter_registrations: Gauge,
+    pub exporter_ebpf_loaded: Gauge,
+    pub exporter_ebpf_map_entries: GaugeVec,
+    pub exporter_ebpf_map_capacity: GaugeVec,
+    /// exporter 自身指标的计数起点（Unix 时间）
+    pub started_at: u64,
 }
 
 impl MetricsRegistry {
-    pub fn new() -> Self {
+    pub fn new(options: MetricsOptions) -> Self {
         let registry = Registry::new();
 
-        // 定义通用的标签
-        let common_labels = &["name", "cmdline","hostname"];
+        // 启用的采集器：按内置顺序，忽略重复项
+        let collectors: Vec<&'static dyn Collector> = COLLECTOR_NAMES
+            .iter()
+            .filter(|name| options.collectors.iter().any(|c| c == *name))
+            .filter_map(|name| collectors::collector_by_name(name))
+            .collect();
 
-        // Gauge metrics
-        let process_up = register_gauge_vec_with_registry!(
-            Opts::new("process_up", "Process is running (1) or down (0)"),
-            common_labels,
+        // Exporter self metrics
+        let exporter_scrape_duration = register_histogram_with_registry!(
+            HistogramOpts::new("process_exporter_scrape_duration_seconds", "Duration of /metrics scrapes in seconds"),
             registry
         ).unwrap();
 
-        let process_pid_info = register_gauge_vec_with_registry!(
-            Opts::new("process_pid_info", "Process PID information"),
-            &["name", "pid", "hostname"],
+        let exporter_collection_errors = register_counter_vec_with_registry!(
+            Opts::new("process_exporter_collection_errors_total", "Failed stats collections per registration"),
+            &["name"],
             registry
         ).unwrap();
 
-        let process_cpu_usage = register_gauge_vec_with_registry!(
-            Opts::new("process_cpu_usage_percent", "Process CPU usage percentage"),
-            common_labels,
+        let exporter_registrations = register_gauge_with_registry!(
+            Opts::new("process_exporter_registrations", "Number of registered processes"),
             registry
         ).unwrap();
 
-        let process_memory_bytes = register_gauge_vec_with_registry!(
-            Opts::new("process_memory_bytes", "Process memory usage in bytes"),
-            common_labels,
+        let exporter_ebpf_loaded = register_gauge_with_registry!(
+            Opts::new("process_exporter_ebpf_loaded", "eBPF programs are loaded (1) or not (0)"),
             registry
         ).unwrap();
 
-        let process_memory_percent = register_gauge_vec_with_registry!(
-            Opts::new("process_memory_percent", "Process memory usage percentage"),
-            common_labels,
+        let exporter_ebpf_map_entries = register_gauge_vec_with_registry!(
+            Opts::new("process_exporter_ebpf_map_entries", "Number of entries in the eBPF map"),
+            &["map"],
             registry
         ).unwrap();
 
-        let process_virtual_memory_bytes = register_gauge_vec_with_registry!(
-            Opts::new("process_virtual_memory_bytes", "Process virtual memory in bytes"),
-            common_labels,
+        let exporter_ebpf_map_capacity = register_gauge_vec_with_registry!(
+            Opts::new("process_exporter_ebpf_map_capacity", "Maximum number of entries of the eBPF map"),
+            &["map"],
             registry
         ).unwrap();
 
-        let process_thread_count = register_gauge_vec_with_registry!(
-            Opts::new("process_thread_count", "Number of threads"),
-            common_labels,
+        // 构建信息为常量，只需注册一次
+        let exporter_build_info = register_gauge_vec_with_registry!(
+            Opts::new("process_exporter_build_info", "Build information of the exporter"),
+            &["version"],
             registry
         ).unwrap();
+        exporter_build_info
+            .with_label_values(&[env!("CARGO_PKG_VERSION")])
+            .set(1.0);
 
-        let process_registered_timestamp = register_gauge_vec_with_registry!(
-            Opts::new("process_registered_timestamp_seconds", "Unix timestamp when process was registered"),
-            common_labels,
-            registry
-        ).unwrap();
+        Self {
+            registry,
+            options,
+            collectors,
+            exporter_scrape_duration,
+            exporter_collection_errors,
+            exporter_registrations,
+            exporter_ebpf_loaded,
+            exporter_ebpf_map_entries,
+            exporter_ebpf_map_capacity,
+            started_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        }
+    }
 
-        let process_last_check_timestamp = register_gauge_vec_with_registry!(
-            Opts::new("process_last_check_timestamp_seconds", "Unix timestamp of last process check"),
-            common_labels,
-            registry
-        ).unwrap();
+    /// 注册进程指标采集器，gather 时直接读取注册表快照
+    ///
+    /// gather 期间会获取状态锁，调用方不能持锁 gather 或渲染
+    pub fn watch(&self, state: AppState) {
+        let collector = ProcessMetrics::new(state, LabelLayout::new(&self.options), self.collectors.clone());
+        if let Err(e) = self.registry.register(Box::new(collector)) {
+            log::error!("Failed to register process metrics: {}", e);
+        }
+    }
 
-        // Counter metrics
-        let process_disk_read_bytes = register_counter_vec_with_registry!(
-            Opts::new("process_disk_read_bytes", "Total disk read bytes"),
-            common_labels,
-            registry
-        ).unwrap();
+    /// 采集器是否已启用
+    pub fn is_collector_enabled(&self, name: &str) -> bool {
+        self.collectors.iter().any(|c| c.name() == name)
+    }
 
-        let process_disk_written_bytes = register_counter_vec_with_registry!(
-            Opts::new("process_disk_written_bytes", "Total disk written bytes"),
-            common_labels,
-            registry
-        ).unwrap();
+    /// 本次采集需要运行的采集器：命令行启用且被 `collect[]` 选中
+    pub fn active_collectors(&self, filter: &ScrapeFilter) -> HashSet<&'static str> {
+        self.collectors.iter()
+            .map(|c| c.name())
+            .filter(|name| filter.includes_collector(name))
+            .collect()
+    }
 
-        let process_network_tx_bytes = register_counter_vec_with_registry!(
-            Opts::new("process_network_tx_bytes", "Network transmitted bytes"),
-            common_labels,
-            registry
-        ).unwrap();
+    /// 采集当前所有指标族，供推送模式使用
+    pub fn gather(&self) -> Vec<MetricFamily> {
+        self.registry.gather()
+    }
 
-        let process_network_rx_bytes = register_counter_vec_with_registry!(
-            Opts::new("process_network_rx_bytes", "Network received bytes"),
-            common_labels,
-            registry
-        ).unwrap();
+    /// 按抓取参数过滤：去掉未选中采集器的指标族，进程指标只保留选中的注册名
+    pub fn gather_filtered(&self, filter: &ScrapeFilter) -> Vec<MetricFamily> {
+        let mut families = self.registry.gather();
+        if filter.is_empty() {
+            return families;
+        }
 
-        let process_network_tx_packets = register_counter_vec_with_registry!(
-            Opts::new("process_network_tx_packets", "Network transmitted packets"),
-            common_labels,
-            registry
-        ).unwrap();
+        let owner = |family: &str| {
+            self.collectors.iter()
+                .find(|c| c.metrics().iter().any(|m| m.name == family))
+                .map(|c| c.name())
+        };
 
-        let process_network_rx_packets = register_counter_vec_with_registry!(
-            Opts::new("process_network_rx_packets", "Network received packets"),
-            common_labels,
-            registry
-        ).unwrap();
+        families.retain_mut(|family| {
+            let Some(collector) = owner(family.get_name()) else {
+                // exporter 自身指标
+                return true;
+            };
+            if !filter.includes_collector(collector) {
+                return false;
+            }
 
-        Self {
-            registry,
-            process_up,
-            process_pid_info,
-            process_cpu_usage,
-            process_memory_bytes,
-            process_memory_percent,
-            process_virtual_memory_bytes,
-            process_thread_count,
-            process_registered_timestamp,
-            process_last_check_timestamp,
-            process_disk_read_bytes,
-            process_disk_written_bytes,
-            process_network_tx_bytes,
-            process_network_rx_bytes,
-            process_network_tx_packets,
-            process_network_rx_packets,
-        }
+            let metrics: Vec<_> = family.take_metric()
+                .into_iter()
+                .filter(|m| {
+                    m.get_label().iter()
+                        .find(|l| l.get_name() == "name")
+                        .is_some_and(|l| filter.includes_process(l.get_value()))
+                })
+                .collect();
+            family.set_metric(metrics.into());
+            !family.get_metric().is_empty()
+        });
+        families
     }
 
-    pub fn render(&self) -> Result<String, Box<dyn std::error::Error>> {
+    pub fn render(&self, filter: &ScrapeFilter) -> Result<String, Box<dyn std::error::Error>> {
         let encoder = TextEncoder::new();
-        let metric_families = self.registry.gather();
+        let metric_families = self.gather_filtered(filter);
         let mut buffer = Vec::new();
         encoder.encode(&metric_families, &mut buffer)?;
         Ok(String::from_utf8(buffer)?)
     }
 
-    pub fn reset_process_metrics(&self, name: &str, cmdline: &str) {
-        // 重置所有该进程的 metrics
-        let hostname = System::host_name().unwrap_or_else(|| "unknown".to_string());
-        let labels = &[name, cmdline, &hostname.clone()];
-
-        // 删除旧的 metric 值
-        let _ = self.process_pid_info.remove_label_values(&[name, "0", &hostname.clone()]);
-        let _ = self.process_up.remove_label_values(labels);
-        let _ = self.process_cpu_usage.remove_label_values(labels);
-        let _ = self.process_memory_bytes.remove_label_values(labels);
-        let _ = self.process_memory_percent.remove_label_values(labels);
-        let _ = self.process_virtual_memory_bytes.remove_label_values(labels);
-        let _ = self.process_thread_count.remove_label_values(labels);
-        let _ = self.process_registered_timestamp.remove_label_values(labels);
-        let _ = self.process_last_check_timestamp.remove_label_values(labels);
+    /// 以 OpenMetrics 格式渲染，`created` 用于生成 counter 的 `_created` 样本
+    pub fn render_openmetrics<F>(&self, filter: &ScrapeFilter, created: F) -> String
+    where
+        F: Fn(&str, &[LabelPair]) -> Option<f64>,
+    {
+        openmetrics::encode(&self.gather_filtered(filter), created)
     }
 }
 
 lazy_static! {
-    pub static ref METRICS: Arc<MetricsRegistry> = Arc::new(MetricsRegistry::new());
-}
\ No newline at end of file
+    pub static ref METRICS: Arc<MetricsRegistry> = Arc::new(
+        MetricsRegistry::new(OPTIONS.get().cloned().unwrap_or_default())
+    );
+}
@@ -0,0 +1,220 @@
+//! OpenMetrics 1.0 文本格式编码
+//!
+//! 与 Prometheus 0.0.4 文本格式的主要区别：
+//! - counter 的样本名带 `_total` 后缀，并可附带 `_created` 时间戳
+//! - 名称以单位结尾的指标输出 `# UNIT` 元数据
+//! - 以 `_info` 结尾且值为 1 的 gauge 输出为 info 类型
+//! - 以 `# EOF` 结尾
+
+use prometheus::proto::{LabelPair, Metric, MetricFamily, MetricType};
+use std::fmt::Write;
+
+/// OpenMetrics 响应的 Content-Type
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// 可识别的单位后缀
+const UNITS: &[&str] = &["seconds", "bytes"];
+
+/// 判断 Accept 头是否接受 OpenMetrics 格式
+pub fn accepts_openmetrics(accept: &str) -> bool {
+    accept.split(',').any(|part| {
+        let mut params = part.split(';').map(str::trim);
+        let media_type = params.next().unwrap_or_default();
+        let rejected = params.any(|p| matches!(p.strip_prefix("q="), Some(q) if q.parse::<f64>() == Ok(0.0)));
+        media_type.eq_ignore_ascii_case("application/openmetrics-text") && !rejected
+    })
+}
+
+/// 将指标族编码为 OpenMetrics 文本
+///
+/// `created` 根据指标名（不含 `_total`）与样本标签返回 counter/histogram 的创建时间（Unix 秒），
+/// 返回 None 时不输出 `_created`
+pub fn encode<F>(families: &[MetricFamily], created: F) -> String
+where
+    F: Fn(&str, &[LabelPair]) -> Option<f64>,
+{
+    let mut out = String::new();
+
+    for family in families {
+        let metric_type = family.get_field_type();
+        let is_info = metric_type == MetricType::GAUGE
+            && family.get_name().ends_with("_info")
+            && family.get_metric().iter().all(|m| m.get_gauge().get_value() == 1.0);
+
+        let name = match metric_type {
+            MetricType::COUNTER => family.get_name().strip_suffix("_total").unwrap_or(family.get_name()),
+            _ if is_info => family.get_name().strip_suffix("_info").unwrap_or(family.get_name()),
+            _ => family.get_name(),
+        };
+        let type_name = match metric_type {
+            MetricType::COUNTER => "counter",
+            MetricType::GAUGE if is_info => "info",
+            MetricType::GAUGE => "gauge",
+            MetricType::HISTOGRAM => "histogram",
+            MetricType::SUMMARY => "summary",
+            MetricType::UNTYPED => "unknown",
+        };
+
+        let _ = writeln!(out, "# TYPE {} {}", name, type_name);
+        if let Some(unit) = UNITS.iter().find(|unit| name.ends_with(&format!("_{}", unit))) {
+            let _ = writeln!(out, "# UNIT {} {}", name, unit);
+        }
+        if !family.get_help().is_empty() {
+            let _ = writeln!(out, "# HELP {} {}", name, escape(family.get_help()));
+        }
+
+        for metric in family.get_metric() {
+            let labels = metric.get_label();
+            match metric_type {
+                MetricType::COUNTER => {
+                    write_sample(&mut out, name, "_total", labels, None, metric.get_counter().get_value());
+                    write_created(&mut out, name, labels, &created);
+                }
+                MetricType::GAUGE if is_info => {
+                    write_sample(&mut out, name, "_info", labels, None, 1.0);
+                }
+                MetricType::GAUGE => {
+                    write_sample(&mut out, name, "", labels, None, metric.get_gauge().get_value());
+                }
+                MetricType::HISTOGRAM => write_histogram(&mut out, name, metric, &created),
+                MetricType::SUMMARY => {
+                    let summary = metric.get_summary();
+                    for quantile in summary.get_quantile() {
+                        let q = format_float(quantile.get_quantile());
+                        write_sample(&mut out, name, "", labels, Some(("quantile", &q)), quantile.get_value());
+                    }
+                    write_sample(&mut out, name, "_sum", labels, None, summary.get_sample_sum());
+                    write_sample(&mut out, name, "_count", labels, None, summary.get_sample_count() as f64);
+                }
+                MetricType::UNTYPED => {
+                    write_sample(&mut out, name, "", labels, None, metric.get_untyped().get_value());
+                }
+            }
+        }
+    }
+
+    out.push_str("# EOF\n");
+    out
+}
+
+fn write_histogram<F>(out: &mut String, name: &str, metric: &Metric, created: &F)
+where
+    F: Fn(&str, &[LabelPair]) -> Option<f64>,
+{
+    let labels = metric.get_label();
+    let histogram = metric.get_histogram();
+
+    for bucket in histogram.get_bucket() {
+        let le = format_float(bucket.get_upper_bound());
+        write_sample(out, name, "_bucket", labels, Some(("le", &le)), bucket.get_cumulative_count() as f64);
+    }
+    // +Inf 桶必须存在
+    if histogram.get_bucket().last().map(|b| b.get_upper_bound()) != Some(f64::INFINITY) {
+        write_sample(out, name, "_bucket", labels, Some(("le", "+Inf")), histogram.get_sample_count() as f64);
+    }
+    write_sample(out, name, "_count", labels, None, histogram.get_sample_count() as f64);
+    write_sample(out, name, "_sum", labels, None, histogram.get_sample_sum());
+    write_created(out, name, labels, created);
+}
+
+fn write_created<F>(out: &mut String, name: &str, labels: &[LabelPair], created: &F)
+where
+    F: Fn(&str, &[LabelPair]) -> Option<f64>,
+{
+    if let Some(timestamp) = created(name, labels) {
+        write_sample(out, name, "_created", labels, None, timestamp);
+    }
+}
+
+fn write_sample(
+    out: &mut String,
+    name: &str,
+    suffix: &str,
+    labels: &[LabelPair],
+    extra: Option<(&str, &str)>,
+    value: f64,
+) {
+    out.push_str(name);
+    out.push_str(suffix);
+
+    let pairs = labels
+        .iter()
+        .map(|l| (l.get_name(), l.get_value()))
+        .chain(extra);
+    let rendered: Vec<String> = pairs
+        .map(|(k, v)| format!("{}=\"{}\"", k, escape(v)))
+        .collect();
+    if !rendered.is_empty() {
+        let _ = write!(out, "{{{}}}", rendered.join(","));
+    }
+
+    let _ = writeln!(out, " {}", format_float(value));
+}
+
+/// OpenMetrics 的浮点数表示：整数值带 `.0`，无穷与 NaN 使用规范写法
+fn format_float(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{:.1}", value)
+    } else {
+        format!("{}", value)
+    }
+}
+
+/// HELP 文本与标签值的转义规则相同
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\n', "\\n").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::{CounterVec, GaugeVec, Histogram, HistogramOpts, Opts, Registry};
+
+    #[test]
+    fn test_encode_openmetrics() {
+        let registry = Registry::new();
+
+        let counter = CounterVec::new(Opts::new("process_disk_read_bytes", "Total disk read bytes"), &["name"]).unwrap();
+        counter.with_label_values(&["nginx"]).inc_by(1024.0);
+        registry.register(Box::new(counter)).unwrap();
+
+        let info = GaugeVec::new(Opts::new("process_cmdline_info", "Command line"), &["name", "cmdline"]).unwrap();
+        info.with_label_values(&["nginx", "nginx: \"master\""]).set(1.0);
+        registry.register(Box::new(info)).unwrap();
+
+        let histogram = Histogram::with_opts(
+            HistogramOpts::new("scrape_duration_seconds", "Scrape duration").buckets(vec![0.5, 1.0]),
+        ).unwrap();
+        histogram.observe(0.25);
+        registry.register(Box::new(histogram)).unwrap();
+
+        let text = encode(&registry.gather(), |name, labels| {
+            let matches = name == "process_disk_read_bytes" && labels.iter().any(|l| l.get_value() == "nginx");
+            matches.then_some(1700000000.0)
+        });
+
+        assert!(text.contains("# TYPE process_disk_read_bytes counter\n"));
+        assert!(text.contains("# UNIT process_disk_read_bytes bytes\n"));
+        assert!(text.contains("process_disk_read_bytes_total{name=\"nginx\"} 1024.0\n"));
+        assert!(text.contains("process_disk_read_bytes_created{name=\"nginx\"} 1700000000.0\n"));
+        assert!(text.contains("# TYPE process_cmdline info\n"));
+        assert!(text.contains("process_cmdline_info{cmdline=\"nginx: \\\"master\\\"\",name=\"nginx\"} 1.0\n"));
+        assert!(text.contains("# UNIT scrape_duration_seconds seconds\n"));
+        assert!(text.contains("scrape_duration_seconds_bucket{le=\"+Inf\"} 1.0\n"));
+        assert!(!text.contains("scrape_duration_seconds_created"));
+        assert!(text.ends_with("# EOF\n"));
+    }
+
+    #[test]
+    fn test_accepts_openmetrics() {
+        assert!(accepts_openmetrics("application/openmetrics-text; version=1.0.0,text/plain;version=0.0.4;q=0.5"));
+        assert!(!accepts_openmetrics("text/plain; version=0.0.4"));
+        assert!(!accepts_openmetrics("application/openmetrics-text;q=0"));
+    }
+}
@@ -0,0 +1,311 @@
+//! 抓取时从注册表快照生成进程指标
+//!
+//! 不再维护全局可变的 GaugeVec/CounterVec：每次 gather 直接读取当前注册的进程，
+//! 注销的进程立即消失，counter 直接输出累计值
+
+use prometheus::core::{Collector as PromCollector, Desc};
+use prometheus::proto::MetricFamily;
+use std::collections::HashMap;
+use sysinfo::System;
+
+use crate::metrics::collectors::{Collector, Families, LabelScheme, Target};
+use crate::metrics::{sanitize_label_name, CommonLabel, MetricsOptions, RESERVED_LABELS};
+use crate::models::ProcessStatus;
+use crate::state::AppState;
+
+/// 进程指标的标签组成
+pub struct LabelLayout {
+    /// 通用标签（name 之外）
+    common_labels: Vec<CommonLabel>,
+    /// 自定义标签（注册时的标签键, 导出的标签名）
+    custom_labels: Vec<(String, String)>,
+    labels_on_series: bool,
+    /// 每个进程输出的对端流量序列上限
+    flow_series_limit: usize,
+    /// 每个进程输出的系统调用序列上限
+    syscall_series_limit: usize,
+}
+
+impl LabelLayout {
+    pub fn new(options: &MetricsOptions) -> Self {
+        // 自定义标签：校验并去重
+        let mut custom_labels: Vec<(String, String)> = Vec::new();
+        for key in &options.label_allowlist {
+            let label = sanitize_label_name(key);
+            // 双下划线开头的标签名由 Prometheus 内部使用
+            if label.starts_with("__") {
+                log::warn!("Ignoring custom label '{}': label names starting with '__' are reserved", key);
+                continue;
+            }
+            if RESERVED_LABELS.contains(&label.as_str()) || custom_labels.iter().any(|(_, l)| *l == label) {
+                log::warn!("Ignoring custom label '{}': conflicts with existing label '{}'", key, label);
+                continue;
+            }
+            custom_labels.push((key.clone(), label));
+        }
+
+        let mut common_labels: Vec<CommonLabel> = Vec::new();
+        for label in &options.common_labels {
+            if !common_labels.contains(label) {
+                common_labels.push(*label);
+            }
+        }
+
+        Self {
+            common_labels,
+            custom_labels,
+            labels_on_series: options.labels_on_series,
+            flow_series_limit: options.flow_series_limit,
+            syscall_series_limit: options.syscall_series_limit,
+        }
+    }
+
+    /// 指标族的标签名，与输出的标签对一样按名称排序
+    fn label_names(&self, scheme: &LabelScheme) -> Vec<String> {
+        let mut names: Vec<&str> = match scheme {
+            LabelScheme::Common(extra) => {
+                let mut names = vec!["name"];
+                names.extend(self.common_labels.iter().map(|l| l.label_name()));
+                if self.labels_on_series {
+                    names.extend(self.custom_labels.iter().map(|(_, l)| l.as_str()));
+                }
+                names.extend(extra.iter());
+                names
+            }
+            LabelScheme::Info(extra) => {
+                let mut names = vec!["name", "hostname"];
+                names.extend(extra.iter());
+                names
+            }
+            LabelScheme::Custom => {
+                let mut names = vec!["name", "hostname"];
+                names.extend(self.custom_labels.iter().map(|(_, l)| l.as_str()));
+                names
+            }
+        };
+        names.sort();
+        names.into_iter().map(str::to_string).collect()
+    }
+
+    /// 为单个进程准备采集上下文
+    fn target<'a>(&'a self, status: &'a ProcessStatus, hostname: &'a str) -> Target<'a> {
+        // 自定义标签值（缺失时为空字符串）
+        let custom_labels: Vec<(&str, String)> = self.custom_labels
+            .iter()
+            .map(|(key, label)| (label.as_str(), status.config.labels.get(key).cloned().unwrap_or_default()))
+            .collect();
+
+        let mut common_labels = vec![("name", status.config.name.clone())];
+        common_labels.extend(self.common_labels.iter().map(|l| (l.label_name(), l.value(status, hostname))));
+        if self.labels_on_series {
+            common_labels.extend(custom_labels.iter().cloned());
+        }
+
+        Target {
+            status,
+            hostname,
+            common_labels,
+            custom_labels,
+            flow_series_limit: self.flow_series_limit,
+            syscall_series_limit: self.syscall_series_limit,
+        }
+    }
+}
+
+/// 注册到 Prometheus Registry 的进程指标采集器
+pub struct ProcessMetrics {
+    state: AppState,
+    layout: LabelLayout,
+    collectors: Vec<&'static dyn Collector>,
+    descs: Vec<Desc>,
+}
+
+impl ProcessMetrics {
+    pub fn new(state: AppState, layout: LabelLayout, collectors: Vec<&'static dyn Collector>) -> Self {
+        let descs = collectors
+            .iter()
+            .flat_map(|c| c.metrics())
+            .map(|spec| {
+                Desc::new(
+                    spec.name.to_string(),
+                    spec.help.to_string(),
+                    layout.label_names(&spec.labels),
+                    HashMap::new(),
+                ).unwrap()
+            })
+            .collect();
+
+        Self { state, layout, collectors, descs }
+    }
+}
+
+impl PromCollector for ProcessMetrics {
+    fn desc(&self) -> Vec<&Desc> {
+        self.descs.iter().collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let hostname = System::host_name().unwrap_or_else(|| "unknown".to_string());
+        let Ok(state) = self.state.lock() else {
+            return Vec::new();
+        };
+
+        let mut families = Families::default();
+        for status in state.processes.values() {
+            let target = self.layout.target(status, &hostname);
+            for collector in &self.collectors {
+                collector.collect(&target, &mut families);
+            }
+        }
+        families.into_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::collectors::collector_by_name;
+    use crate::metrics::COLLECTOR_NAMES;
+    use crate::models::{FlowStats, LatencyHistogram, ProcessConfig, ProcessStats, TcpConnectStats};
+    use crate::state::new_state;
+    use prometheus::proto::MetricType;
+    use prometheus::Registry;
+
+    fn status(name: &str) -> ProcessStatus {
+        let mut config = ProcessConfig::new(name, name);
+        config.labels.insert("team".to_string(), "infra".to_string());
+        config.ports = vec![8080];
+        let mut status = ProcessStatus::new(config);
+        status.pid = Some(42);
+        status.stats = ProcessStats { memory_bytes: 4096, disk_read_bytes: 1000, ..ProcessStats::default() };
+        status
+    }
+
+    fn registry(state: &AppState, options: &MetricsOptions) -> Registry {
+        let collectors = COLLECTOR_NAMES.iter().filter_map(|n| collector_by_name(n)).collect();
+        let registry = Registry::new();
+        registry.register(Box::new(ProcessMetrics::new(state.clone(), LabelLayout::new(options), collectors))).unwrap();
+        registry
+    }
+
+    #[test]
+    fn counters_are_cumulative_and_unregistered_processes_disappear() {
+        let state = new_state();
+        state.lock().unwrap().processes.insert("nginx".into(), status("nginx"));
+        let registry = registry(&state, &MetricsOptions::default());
+
+        for _ in 0..2 {
+            let families = registry.gather();
+            let disk = families.iter().find(|f| f.get_name() == "process_disk_read_bytes").unwrap();
+            assert_eq!(disk.get_field_type(), MetricType::COUNTER);
+            assert_eq!(disk.get_metric()[0].get_counter().get_value(), 1000.0);
+        }
+
+        state.lock().unwrap().processes.remove("nginx");
+        assert!(registry.gather().is_empty());
+    }
+
+    #[test]
+    fn labels_follow_layout() {
+        let state = new_state();
+        state.lock().unwrap().processes.insert("nginx".into(), status("nginx"));
+        let options = MetricsOptions {
+            common_labels: vec![CommonLabel::Pid],
+            label_allowlist: vec!["team".into()],
+            labels_on_series: true,
+            ..MetricsOptions::default()
+        };
+        let families = registry(&state, &options).gather();
+
+        let labels = |family: &str| -> Vec<(String, String)> {
+            let family = families.iter().find(|f| f.get_name() == family).unwrap();
+            family.get_metric()[0].get_label().iter()
+                .map(|l| (l.get_name().to_string(), l.get_value().to_string()))
+                .collect()
+        };
+        let pair = |k: &str, v: &str| (k.to_string(), v.to_string());
+
+        assert_eq!(labels("process_up"), vec![pair("name", "nginx"), pair("pid", "42"), pair("team", "infra")]);
+        assert_eq!(
+            labels("process_port_listening"),
+            vec![pair("name", "nginx"), pair("pid", "42"), pair("port", "8080"), pair("team", "infra")]
+        );
+        let info: Vec<String> = labels("process_labels_info").into_iter().map(|(k, _)| k).collect();
+        assert_eq!(info, vec!["hostname", "name", "team"]);
+    }
+
+    #[test]
+    fn reserved_custom_labels_are_ignored() {
+        let options = MetricsOptions {
+            label_allowlist: vec!["team".into(), "le".into(), "quantile".into(), "__meta".into(), "..x".into(), "team".into()],
+            ..MetricsOptions::default()
+        };
+        let layout = LabelLayout::new(&options);
+        let labels: Vec<&str> = layout.custom_labels.iter().map(|(_, l)| l.as_str()).collect();
+        assert_eq!(labels, vec!["team"]);
+    }
+
+    #[test]
+    fn tcp_metrics_are_exported() {
+        let state = new_state();
+        let mut nginx = status("nginx");
+        nginx.stats.tcp_retransmits = 7;
+        nginx.stats.tcp_rtt = Some(LatencyHistogram { buckets: vec![0, 2, 1, 1], sum_us: 30, count: 4 });
+        nginx.stats.tcp_connects = TcpConnectStats { attempts: 5, refused: 2, accepted: 9, ..TcpConnectStats::default() };
+        state.lock().unwrap().processes.insert("nginx".into(), nginx);
+        let families = registry(&state, &MetricsOptions::default()).gather();
+
+        let rtt = families.iter().find(|f| f.get_name() == "process_tcp_rtt_seconds").unwrap();
+        let histogram = rtt.get_metric()[0].get_histogram();
+        assert_eq!(histogram.get_sample_count(), 4);
+        let cumulative: Vec<u64> = histogram.get_bucket().iter().map(|b| b.get_cumulative_count()).collect();
+        assert_eq!(cumulative, vec![0, 2, 3]);
+
+        let retransmits = families.iter().find(|f| f.get_name() == "process_tcp_retransmits_total").unwrap();
+        assert_eq!(retransmits.get_metric()[0].get_counter().get_value(), 7.0);
+
+        let failures = families.iter().find(|f| f.get_name() == "process_tcp_connect_failures_total").unwrap();
+        let refused = failures.get_metric().iter()
+            .find(|m| m.get_label().iter().any(|l| l.get_name() == "reason" && l.get_value() == "refused"))
+            .unwrap();
+        assert_eq!(refused.get_counter().get_value(), 2.0);
+        // 没有成功建连时不输出耗时直方图
+        assert!(!families.iter().any(|f| f.get_name() == "process_tcp_connect_duration_seconds"));
+    }
+
+    #[test]
+    fn sched_histograms_are_exported() {
+        let state = new_state();
+        let mut nginx = status("nginx");
+        nginx.stats.off_cpu = Some(LatencyHistogram { buckets: vec![1, 0, 3], sum_us: 2_000_000, count: 4 });
+        state.lock().unwrap().processes.insert("nginx".into(), nginx);
+        let families = registry(&state, &MetricsOptions::default()).gather();
+
+        let off_cpu = families.iter().find(|f| f.get_name() == "process_off_cpu_seconds").unwrap();
+        let histogram = off_cpu.get_metric()[0].get_histogram();
+        assert_eq!(histogram.get_sample_count(), 4);
+        assert_eq!(histogram.get_sample_sum(), 2.0);
+        // 未开启或没有采样时不输出
+        assert!(!families.iter().any(|f| f.get_name() == "process_run_queue_latency_seconds"));
+    }
+
+    #[test]
+    fn flow_series_are_bounded() {
+        let state = new_state();
+        let mut nginx = status("nginx");
+        nginx.flows = (0..5u16)
+            .map(|i| FlowStats { protocol: "tcp".into(), remote_port: 5000 + i, tx_bytes: 100, ..FlowStats::default() })
+            .collect();
+        state.lock().unwrap().processes.insert("nginx".into(), nginx);
+
+        let count = |limit: usize| {
+            let options = MetricsOptions { flow_series_limit: limit, ..MetricsOptions::default() };
+            registry(&state, &options).gather().iter()
+                .find(|f| f.get_name() == "process_flow_tx_bytes")
+                .map_or(0, |f| f.get_metric().len())
+        };
+        assert_eq!(count(0), 0);
+        assert_eq!(count(2), 2);
+        assert_eq!(count(10), 5);
+    }
+}
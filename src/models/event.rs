@@ -0,0 +1,44 @@
+use serde::Serialize;
+
+/// 进程生命周期事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessEventKind {
+    Exec,
+    Fork,
+    Exit,
+}
+
+impl ProcessEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProcessEventKind::Exec => "exec",
+            ProcessEventKind::Fork => "fork",
+            ProcessEventKind::Exit => "exit",
+        }
+    }
+}
+
+/// eBPF 上报的进程生命周期事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProcessEvent {
+    pub kind: ProcessEventKind,
+    /// 进程 PID（tgid）
+    pub pid: i32,
+    /// fork 产生的子进程 PID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub child_pid: Option<i32>,
+    /// 事件发生时的进程名（exec 后为新程序名）
+    pub comm: String,
+    /// 收到事件的时间戳（Unix 时间）
+    pub timestamp: u64,
+}
+
+/// 已归属到注册进程的生命周期事件
+#[derive(Debug, Clone, Serialize)]
+pub struct NamedProcessEvent {
+    /// 所属注册进程名
+    pub name: String,
+    #[serde(flatten)]
+    pub event: ProcessEvent,
+}
@@ -0,0 +1,25 @@
+use serde::Serialize;
+
+/// 进程与单个对端之间的流量（IPv4）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FlowStats {
+    /// 协议名（tcp / udp）
+    pub protocol: String,
+    /// 对端地址
+    pub remote_address: String,
+    /// 对端端口
+    pub remote_port: u16,
+    /// 本地端口
+    pub local_port: u16,
+    pub tx_bytes: u64,
+    pub rx_bytes: u64,
+    pub tx_packets: u64,
+    pub rx_packets: u64,
+}
+
+impl FlowStats {
+    /// 收发总字节数，用于排序
+    pub fn total_bytes(&self) -> u64 {
+        self.tx_bytes.saturating_add(self.rx_bytes)
+    }
+}
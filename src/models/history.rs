@@ -0,0 +1,60 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::models::ProcessStats;
+
+/// 单个历史采样点
+#[derive(Debug, Clone, Serialize)]
+pub struct HistorySample {
+    /// 采样时间戳（Unix 时间）
+    pub timestamp: u64,
+    /// 是否正在运行
+    pub is_running: bool,
+    /// 进程 ID
+    pub pid: Option<i32>,
+    /// 资源使用统计
+    pub stats: ProcessStats,
+}
+
+/// 单个注册进程的历史采样（容量固定的环形缓冲区）
+#[derive(Debug, Clone)]
+pub struct ProcessHistory {
+    samples: VecDeque<HistorySample>,
+    capacity: usize,
+}
+
+impl ProcessHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity.min(4096)),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// 追加采样点，超出容量时丢弃最旧的
+    ///
+    /// 与最新采样点时间戳相同（后台采集任务尚未刷新）时忽略，避免出现间隔为 0 的重复点
+    pub fn push(&mut self, sample: HistorySample) {
+        if self.samples.back().is_some_and(|last| last.timestamp == sample.timestamp) {
+            return;
+        }
+        if self.samples.len() >= self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// 时间戳不早于 since 的采样点（按时间升序）
+    pub fn since(&self, since: u64) -> impl Iterator<Item = &HistorySample> {
+        self.samples.iter().filter(move |s| s.timestamp >= since)
+    }
+}
+
+/// 历史查询返回的数据点：采样值 + 与上一个点之间的计数器速率（每秒）
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryPoint {
+    #[serde(flatten)]
+    pub sample: HistorySample,
+    /// 计数器速率，首个点、计数器回绕（如进程重启）时为空
+    pub rates: BTreeMap<&'static str, f64>,
+}
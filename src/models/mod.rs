@@ -1,5 +1,17 @@
 pub mod process;
 pub mod stats;
+pub mod probe;
+pub mod history;
+pub mod restart;
+pub mod flow;
+pub mod syscall;
+pub mod event;
 
 pub use process::{ProcessConfig, ProcessStatus};
-pub use stats::ProcessStats;
\ No newline at end of file
+pub use stats::{ProcessStats, ListeningPort, ProtocolTraffic, LatencyHistogram, TcpConnectStats, BlockDeviceStats};
+pub use probe::{ProbeConfig, ProbeKind, ProbeResult};
+pub use history::{HistoryPoint, HistorySample, ProcessHistory};
+pub use restart::{FlapPolicy, RestartTracker};
+pub use flow::FlowStats;
+pub use syscall::SyscallUsage;
+pub use event::{NamedProcessEvent, ProcessEvent, ProcessEventKind};
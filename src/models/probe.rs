@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+fn default_expected_status() -> u16 {
+    200
+}
+
+fn default_timeout_ms() -> u64 {
+    5000
+}
+
+/// 探测方式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ProbeKind {
+    /// TCP 连接探测，例如 "127.0.0.1:8080"
+    Tcp { address: String },
+    /// HTTP GET 探测，返回码需等于 expected_status
+    Http {
+        url: String,
+        #[serde(default = "default_expected_status")]
+        expected_status: u16,
+    },
+    /// 本地命令探测，退出码为 0 视为成功
+    Exec {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+impl ProbeKind {
+    /// 探测类型名称
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ProbeKind::Tcp { .. } => "tcp",
+            ProbeKind::Http { .. } => "http",
+            ProbeKind::Exec { .. } => "exec",
+        }
+    }
+}
+
+/// 健康探测配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeConfig {
+    /// 探测名称（为空时使用探测类型）
+    #[serde(default)]
+    pub name: String,
+    /// 探测方式
+    #[serde(flatten)]
+    pub kind: ProbeKind,
+    /// 超时时间（毫秒）
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl ProbeConfig {
+    /// 用于标签与结果索引的探测名称
+    pub fn label(&self) -> &str {
+        if self.name.is_empty() {
+            self.kind.type_name()
+        } else {
+            &self.name
+        }
+    }
+}
+
+/// 单次探测结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbeResult {
+    /// 探测类型
+    #[serde(rename = "type")]
+    pub probe_type: String,
+    /// 是否成功
+    pub success: bool,
+    /// 探测耗时（秒）
+    pub duration_seconds: f64,
+    /// 探测时间戳（Unix 时间）
+    pub checked_at: u64,
+    /// 失败原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
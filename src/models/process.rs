@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 pub use crate::models::stats::ProcessStats;
+use crate::models::probe::{ProbeConfig, ProbeResult};
+use crate::models::restart::RestartTracker;
+use crate::models::flow::FlowStats;
+use crate::models::syscall::SyscallUsage;
 
 /// 进程配置信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +16,12 @@ pub struct ProcessConfig {
     /// 自定义标签
     #[serde(default)]
     pub labels: HashMap<String, String>,
+    /// 期望监听的端口
+    #[serde(default)]
+    pub ports: Vec<u16>,
+    /// 健康探测
+    #[serde(default)]
+    pub probes: Vec<ProbeConfig>,
 }
 
 /// 进程运行状态
@@ -27,6 +37,53 @@ pub struct ProcessStatus {
     pub is_running: bool,
     /// 进程 ID
     pub pid: Option<i32>,
+    /// 最近一次运行时的进程名（/proc/<pid>/comm），进程退出后保留，供内核过滤 exec 事件
+    pub comm: String,
     /// 进程资源使用统计
     pub stats: ProcessStats,
-}
\ No newline at end of file
+    /// 当前 PID 加入 eBPF 白名单的时间戳，eBPF 计数从此开始；未加入时为 0
+    pub whitelisted_at: u64,
+    /// 最近一次探测结果（探测名称 -> 结果）
+    pub probe_results: HashMap<String, ProbeResult>,
+    /// 重启记录
+    pub restarts: RestartTracker,
+    /// 按总流量排序的对端流量（最多保留 MAX_FLOWS_PER_PROCESS 条）
+    pub flows: Vec<FlowStats>,
+    /// 按调用次数排序的系统调用统计（需开启 --syscall-tracing）
+    pub syscalls: Vec<SyscallUsage>,
+}
+
+#[cfg(test)]
+impl ProcessConfig {
+    /// 测试用：只有名称和命令行模式的配置
+    pub fn new(name: &str, cmdline: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            cmdline: cmdline.to_string(),
+            labels: HashMap::new(),
+            ports: Vec::new(),
+            probes: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl ProcessStatus {
+    /// 测试用：运行中、尚无 PID 与统计的进程状态，按需修改字段
+    pub fn new(config: ProcessConfig) -> Self {
+        Self {
+            config,
+            registered_at: 0,
+            last_check: 0,
+            is_running: true,
+            pid: None,
+            comm: String::new(),
+            stats: ProcessStats::default(),
+            whitelisted_at: 0,
+            probe_results: HashMap::new(),
+            restarts: RestartTracker::default(),
+            flows: Vec::new(),
+            syscalls: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// 抖动判定：窗口内重启次数达到阈值即视为抖动
+#[derive(Debug, Clone, Copy)]
+pub struct FlapPolicy {
+    /// 窗口内的重启次数阈值
+    pub threshold: usize,
+    /// 窗口长度（秒）
+    pub window: u64,
+}
+
+impl Default for FlapPolicy {
+    fn default() -> Self {
+        Self { threshold: 3, window: 300 }
+    }
+}
+
+/// 注册进程的重启记录，按解析到的 PID 变化判断
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RestartTracker {
+    /// 累计重启次数
+    pub total: u64,
+    /// 最近一次重启时间（Unix 时间）
+    pub last_restart: Option<u64>,
+    /// 抖动窗口内的重启时间（升序）
+    pub recent_restarts: VecDeque<u64>,
+    /// 是否处于抖动状态
+    pub flapping: bool,
+    /// 最近一次解析到的 PID，进程停止期间保留，用于识别重新拉起
+    #[serde(skip)]
+    last_pid: Option<i32>,
+}
+
+impl RestartTracker {
+    pub fn new(pid: Option<i32>) -> Self {
+        Self {
+            last_pid: pid,
+            ..Self::default()
+        }
+    }
+
+    /// 记录一次 PID 解析结果，出现与之前不同的 PID 时计为一次重启
+    ///
+    /// 注册时进程未运行、之后第一次启动不算重启；每次调用都会按窗口重新判定抖动
+    pub fn observe(&mut self, pid: Option<i32>, now: u64, policy: &FlapPolicy) -> bool {
+        let restarted = match pid {
+            Some(pid) => {
+                let restarted = self.last_pid.is_some_and(|last| last != pid);
+                self.last_pid = Some(pid);
+                restarted
+            }
+            None => false,
+        };
+
+        if restarted {
+            self.total += 1;
+            self.last_restart = Some(now);
+            self.recent_restarts.push_back(now);
+        }
+
+        let since = now.saturating_sub(policy.window);
+        while self.recent_restarts.front().is_some_and(|t| *t < since) {
+            self.recent_restarts.pop_front();
+        }
+        self.flapping = self.recent_restarts.len() >= policy.threshold;
+
+        restarted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: FlapPolicy = FlapPolicy { threshold: 2, window: 60 };
+
+    #[test]
+    fn counts_pid_changes_including_across_downtime() {
+        let mut tracker = RestartTracker::new(Some(100));
+        assert!(!tracker.observe(Some(100), 10, &POLICY));
+        assert!(tracker.observe(Some(200), 20, &POLICY));
+        // 停止期间不计数，重新拉起后计一次
+        assert!(!tracker.observe(None, 30, &POLICY));
+        assert!(tracker.observe(Some(300), 40, &POLICY));
+
+        assert_eq!(tracker.total, 2);
+        assert_eq!(tracker.last_restart, Some(40));
+    }
+
+    #[test]
+    fn first_start_after_registration_is_not_a_restart() {
+        let mut tracker = RestartTracker::new(None);
+        assert!(!tracker.observe(Some(100), 10, &POLICY));
+        assert_eq!(tracker.total, 0);
+        assert_eq!(tracker.last_restart, None);
+    }
+
+    #[test]
+    fn flapping_follows_restarts_in_window() {
+        let mut tracker = RestartTracker::new(Some(1));
+        tracker.observe(Some(2), 100, &POLICY);
+        assert!(!tracker.flapping);
+
+        tracker.observe(Some(3), 130, &POLICY);
+        assert!(tracker.flapping);
+        assert_eq!(tracker.recent_restarts, [100, 130]);
+
+        // 第一次重启滑出窗口后恢复
+        tracker.observe(Some(3), 161, &POLICY);
+        assert!(!tracker.flapping);
+        assert_eq!(tracker.recent_restarts, [130]);
+        assert_eq!(tracker.total, 2);
+    }
+}
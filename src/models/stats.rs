@@ -1,10 +1,85 @@
 use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 进程监听的端口
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ListeningPort {
+    /// 协议（tcp / udp）
+    pub protocol: String,
+    /// 端口号
+    pub port: u16,
+}
+
+/// 单个协议的网络流量（eBPF 统计）
+#[derive(Debug, Clone, Copy, Serialize, Default, PartialEq, Eq)]
+pub struct ProtocolTraffic {
+    pub tx_bytes: u64,
+    pub rx_bytes: u64,
+    pub tx_packets: u64,
+    pub rx_packets: u64,
+}
+
+/// 时延分布（eBPF log2 直方图），第 i 个桶为 [2^i, 2^(i+1)) 微秒，最后一个桶不设上界
+#[derive(Debug, Clone, Serialize, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    pub buckets: Vec<u64>,
+    pub sum_us: u64,
+    pub count: u64,
+}
+
+impl LatencyHistogram {
+    /// Prometheus 风格的累积桶：(上界秒数, 累计次数)，不含 +Inf
+    pub fn cumulative_buckets(&self) -> Vec<(f64, u64)> {
+        let bounded = self.buckets.len().saturating_sub(1);
+        self.buckets[..bounded]
+            .iter()
+            .enumerate()
+            .scan(0u64, |total, (i, count)| {
+                *total += count;
+                Some(((1u64 << (i + 1)) as f64 / 1e6, *total))
+            })
+            .collect()
+    }
+}
+
+/// 进程在单个块设备上的 I/O（eBPF block_rq_issue / block_rq_complete 统计）
+#[derive(Debug, Clone, Serialize, Default, PartialEq, Eq)]
+pub struct BlockDeviceStats {
+    /// 设备名（如 sda、nvme0n1）
+    pub device: String,
+    /// 完成的请求数
+    pub requests: u64,
+    /// 请求字节数
+    pub bytes: u64,
+    /// 从下发到完成的耗时
+    pub latency: LatencyHistogram,
+}
+
+/// TCP 建连统计（eBPF inet_sock_set_state）
+#[derive(Debug, Clone, Serialize, Default, PartialEq, Eq)]
+pub struct TcpConnectStats {
+    /// 主动连接次数
+    pub attempts: u64,
+    /// 被对端拒绝的连接
+    pub refused: u64,
+    /// 超时的连接
+    pub timeout: u64,
+    /// 其他原因失败的连接（如进程主动放弃）
+    pub other: u64,
+    /// 被动接受的连接
+    pub accepted: u64,
+    /// 主动连接的建连耗时，没有成功的连接时为空
+    pub latency: Option<LatencyHistogram>,
+}
 
 #[derive(Debug, Clone, Serialize, Default)]
 pub struct ProcessStats {
     /// CPU 使用率 (百分比，0-100)
     pub cpu_usage: f32,
 
+    /// 累计 CPU 时间（秒，所有核心之和）
+    pub cpu_time_seconds: f64,
+
     /// 内存使用量 (字节)
     pub memory_bytes: u64,
 
@@ -14,20 +89,65 @@ pub struct ProcessStats {
     /// 虚拟内存使用量 (字节)
     pub virtual_memory_bytes: u64,
 
-    /// 磁盘读取字节数
+    /// 累计磁盘读取字节数
     pub disk_read_bytes: u64,
 
-    /// 磁盘写入字节数
+    /// 累计磁盘写入字节数
     pub disk_written_bytes: u64,
 
     /// 线程数
     pub thread_count: usize,
 
+    /// 进程启动时间（Unix 时间）
+    pub start_time: u64,
+
     // ebpf相关状态
     pub network_tx_bytes: u64,
     pub network_rx_bytes: u64,
     pub network_tx_packets: u64,
     pub network_rx_packets: u64,
+
+    /// 按协议（tcp / udp）划分的网络流量，上面的 network_* 为各协议之和
+    pub network_by_protocol: BTreeMap<String, ProtocolTraffic>,
+
+    /// 进程独占 cgroup 的网络流量（cgroup_skb 统计，按实际 skb 计数，含协议头），未启用或未挂载时为空
+    pub network_skb: Option<ProtocolTraffic>,
+
+    /// network_skb 对应的 cgroup v2 路径
+    pub cgroup: String,
+
+    /// 累计 TCP 重传次数（eBPF tcp_retransmit_skb）
+    pub tcp_retransmits: u64,
+
+    /// TCP 平滑 RTT 分布，没有采样时为空
+    pub tcp_rtt: Option<LatencyHistogram>,
+
+    /// TCP 建连统计
+    pub tcp_connects: TcpConnectStats,
+
+    /// 各块设备上的 I/O 请求统计（只含进程自己下发的请求，不含内核线程回写）
+    pub block_io: Vec<BlockDeviceStats>,
+
+    /// 线程阻塞（睡眠）到被唤醒的时间分布（eBPF sched_switch / sched_wakeup），未开启或没有采样时为空
+    pub off_cpu: Option<LatencyHistogram>,
+
+    /// 线程可运行到真正上 CPU 的排队时间分布
+    pub run_queue_latency: Option<LatencyHistogram>,
+
+    /// 各 TCP 状态的连接数（状态名 -> 数量）
+    pub tcp_connections: BTreeMap<String, u64>,
+
+    /// 监听中的端口列表
+    pub listening_ports: Vec<ListeningPort>,
+
+    /// 进程所属用户
+    pub user: String,
+
+    /// 可执行文件路径
+    pub exe: String,
+
+    /// 容器 ID（不在容器中时为空）
+    pub container_id: String,
 }
 
 impl ProcessStats {
@@ -36,8 +156,27 @@ impl ProcessStats {
         Self::default()
     }
 
+    /// 判断是否在指定端口上监听（任意协议）
+    pub fn is_listening_on(&self, port: u16) -> bool {
+        self.listening_ports.iter().any(|p| p.port == port)
+    }
+
     /// 判断是否有有效数据
     pub fn is_valid(&self) -> bool {
         self.cpu_usage > 0.0 || self.memory_bytes > 0
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cumulative_buckets() {
+        let rtt = LatencyHistogram { buckets: vec![1, 0, 2, 5], sum_us: 100, count: 8 };
+        assert_eq!(
+            rtt.cumulative_buckets(),
+            vec![(0.000002, 1), (0.000004, 1), (0.000008, 3)]
+        );
+    }
+}
@@ -0,0 +1,24 @@
+use serde::Serialize;
+
+/// 进程对单个系统调用的调用与错误次数
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SyscallUsage {
+    /// 系统调用号
+    pub nr: u32,
+    /// 系统调用名，未知时为 syscall_<nr>
+    pub name: String,
+    /// 调用次数
+    pub count: u64,
+    /// 返回错误码的次数
+    pub errors: u64,
+}
+
+impl SyscallUsage {
+    /// 错误率（0 ~ 1）
+    pub fn error_rate(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        (self.errors as f64 / self.count as f64).min(1.0)
+    }
+}
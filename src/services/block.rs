@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::fs;
+
+use crate::models::{BlockDeviceStats, LatencyHistogram};
+use crate::services::ebpf_loader::{BlockIoKey, BlockIoStats};
+
+/// 将 eBPF 的块设备 I/O 条目按 PID 分组，每个进程按设备名排序
+pub fn block_io_by_pid<I>(raw: I) -> HashMap<u32, Vec<BlockDeviceStats>>
+where
+    I: IntoIterator<Item = (BlockIoKey, BlockIoStats)>,
+{
+    let mut names: HashMap<u32, String> = HashMap::new();
+    let mut grouped: HashMap<u32, Vec<BlockDeviceStats>> = HashMap::new();
+    for (key, stats) in raw {
+        let device = names.entry(key.dev).or_insert_with(|| device_name(key.dev)).clone();
+        grouped.entry(key.tgid).or_default().push(BlockDeviceStats {
+            device,
+            requests: stats.requests,
+            bytes: stats.bytes,
+            latency: LatencyHistogram::from(stats.latency),
+        });
+    }
+
+    for devices in grouped.values_mut() {
+        devices.sort_by(|a, b| a.device.cmp(&b.device));
+    }
+    grouped
+}
+
+/// 块设备名（如 sda、nvme0n1），找不到时为 "主设备号:次设备号"
+fn device_name(dev: u32) -> String {
+    let (major, minor) = split_dev(dev);
+    fs::read_link(format!("/sys/dev/block/{}:{}", major, minor))
+        .ok()
+        .and_then(|target| target.file_name().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| format!("{}:{}", major, minor))
+}
+
+/// 内核 dev_t 为 主设备号 << 20 | 次设备号
+fn split_dev(dev: u32) -> (u32, u32) {
+    (dev >> 20, dev & 0xFFFFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_dev() {
+        assert_eq!(split_dev((8 << 20) | 16), (8, 16));
+        assert_eq!(split_dev((259 << 20) | 1), (259, 1));
+    }
+}
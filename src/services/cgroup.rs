@@ -0,0 +1,87 @@
+use std::fs;
+use std::path::PathBuf;
+
+/// cgroup v2 挂载点
+pub const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// 向上追溯父进程的最大层数
+const MAX_ANCESTRY_DEPTH: usize = 64;
+
+/// 进程独占的 cgroup v2 路径（如 `/system.slice/nginx.service`）
+///
+/// 根 cgroup，或其中还有该进程及其子孙之外的进程时返回 None
+pub fn owned_cgroup(pid: i32) -> Option<String> {
+    let content = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    let cgroup = unified_path(&content)?;
+    if cgroup == "/" {
+        return None;
+    }
+
+    let procs = fs::read_to_string(cgroup_dir(cgroup).join("cgroup.procs")).ok()?;
+    let owned = procs
+        .lines()
+        .filter_map(|line| line.trim().parse::<i32>().ok())
+        .all(|member| is_descendant(member, pid));
+    owned.then(|| cgroup.to_string())
+}
+
+/// cgroup 在 cgroupfs 中的目录
+pub fn cgroup_dir(cgroup: &str) -> PathBuf {
+    PathBuf::from(CGROUP_ROOT).join(cgroup.trim_start_matches('/'))
+}
+
+/// /proc/<pid>/cgroup 中 cgroup v2 的路径（`0::/path`），纯 v1 系统上没有
+fn unified_path(content: &str) -> Option<&str> {
+    content.lines().find_map(|line| line.strip_prefix("0::"))
+}
+
+/// `pid` 是否为 `ancestor` 本身或其子孙进程
+fn is_descendant(pid: i32, ancestor: i32) -> bool {
+    let mut current = pid;
+    for _ in 0..MAX_ANCESTRY_DEPTH {
+        if current == ancestor {
+            return true;
+        }
+        match parent_pid(current) {
+            Some(parent) if parent > 0 => current = parent,
+            _ => return false,
+        }
+    }
+    false
+}
+
+fn parent_pid(pid: i32) -> Option<i32> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_ppid(&stat)
+}
+
+/// 解析 /proc/<pid>/stat 中的父进程 ID，进程名可能包含空格和括号，从最后一个 ')' 之后开始
+fn parse_ppid(stat: &str) -> Option<i32> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    // 字段依次为 state、ppid
+    rest.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_path() {
+        let hybrid = "12:memory:/system.slice/nginx.service\n0::/system.slice/nginx.service\n";
+        assert_eq!(unified_path(hybrid), Some("/system.slice/nginx.service"));
+        assert_eq!(unified_path("0::/\n"), Some("/"));
+        assert_eq!(unified_path("4:cpu:/docker/abc\n"), None);
+    }
+
+    #[test]
+    fn test_parse_ppid() {
+        assert_eq!(parse_ppid("1234 (my (odd) proc) S 1 1234 1234 0 -1"), Some(1));
+        assert_eq!(parse_ppid("garbage"), None);
+    }
+
+    #[test]
+    fn test_cgroup_dir() {
+        assert_eq!(cgroup_dir("/system.slice/a.service"), PathBuf::from("/sys/fs/cgroup/system.slice/a.service"));
+    }
+}
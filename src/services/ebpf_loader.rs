@@ -1,14 +1,89 @@
 use aya::{
     include_bytes_aligned,
-    maps::HashMap as AyaHashMap,
-    programs::KProbe,
-    Ebpf,
+    maps::{HashMap as AyaHashMap, IterableMap, MapData, RingBuf},
+    maps::Array,
+    programs::{
+        cgroup_skb::CgroupSkbLinkId, CgroupAttachMode, CgroupSkb, CgroupSkbAttachType, KProbe, TracePoint,
+    },
+    Ebpf, Pod,
 };
 use aya_log::EbpfLogger;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::os::unix::fs::MetadataExt;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::unix::AsyncFd;
+use tokio::sync::{broadcast, Mutex};
 use log::{info, warn};
 
+use crate::models::{LatencyHistogram, ProcessEvent, ProcessEventKind, ProtocolTraffic, TcpConnectStats};
+use crate::services::cgroup::cgroup_dir;
+
+/// eBPF 统计的协议：(协议号, 协议名)，协议号与 IPPROTO_* 一致
+pub const PROTOCOLS: &[(u32, &str)] = &[(6, "tcp"), (17, "udp")];
+
+/// 必须挂载的 kretprobe
+const REQUIRED_PROBES: &[&str] = &["tcp_sendmsg", "tcp_recvmsg"];
+
+/// 可选的 kretprobe，挂载失败时只告警（如内核未启用 IPv6）
+const OPTIONAL_PROBES: &[&str] = &["udp_sendmsg", "udp_recvmsg", "udpv6_sendmsg", "udpv6_recvmsg"];
+
+/// tracepoint 程序：(类别, 名称, 需要解析偏移的字段)
+///
+/// 程序名与 tracepoint 名相同，字段偏移依次写入 TRACEPOINT_OFFSETS，顺序需与 eBPF 程序中的下标一致
+const TRACEPOINTS: &[(&str, &str, &[&str])] = &[
+    ("tcp", "tcp_retransmit_skb", &["skaddr"]),
+    ("tcp", "tcp_probe", &["skaddr", "srtt"]),
+    ("sock", "inet_sock_set_state", &["skaddr", "oldstate", "newstate", "protocol"]),
+    ("tcp", "tcp_receive_reset", &["skaddr"]),
+    ("block", "block_rq_issue", &["dev", "sector", "bytes"]),
+    ("block", "block_rq_complete", &["dev", "sector"]),
+];
+
+/// 系统调用 tracepoint，每次系统调用都会触发，由 --syscall-tracing 开启；偏移下标接在 TRACEPOINTS 之后
+const SYSCALL_TRACEPOINTS: &[(&str, &str, &[&str])] = &[
+    ("raw_syscalls", "sys_enter", &["id"]),
+    ("raw_syscalls", "sys_exit", &["id", "ret"]),
+];
+
+/// 调度 tracepoint，每次上下文切换都会触发，由 --sched-tracing 开启；偏移下标接在 SYSCALL_TRACEPOINTS 之后
+const SCHED_TRACEPOINTS: &[(&str, &str, &[&str])] = &[
+    ("sched", "sched_switch", &["prev_state", "next_pid"]),
+    ("sched", "sched_wakeup", &["pid"]),
+];
+
+/// 进程生命周期 tracepoint，偏移下标接在 SCHED_TRACEPOINTS 之后
+const LIFECYCLE_TRACEPOINTS: &[(&str, &str, &[&str])] = &[
+    ("sched", "sched_process_exec", &[]),
+    ("sched", "sched_process_fork", &["child_pid"]),
+    ("sched", "sched_process_exit", &[]),
+];
+
+/// 生命周期事件广播的缓冲条数，订阅者落后超过该值时丢弃旧事件
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// tracefs 可能的挂载点
+const TRACEFS_ROOTS: &[&str] = &["/sys/kernel/tracing", "/sys/kernel/debug/tracing"];
+
+/// log2 直方图的桶数，与 eBPF 程序一致
+const HISTOGRAM_BUCKETS: usize = 24;
+
+/// cgroup_skb 程序：(程序名, 挂载方向)
+const CGROUP_SKB_PROGRAMS: &[(&str, CgroupSkbAttachType)] = &[
+    ("cgroup_skb_ingress", CgroupSkbAttachType::Ingress),
+    ("cgroup_skb_egress", CgroupSkbAttachType::Egress),
+];
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NetworkStatsKey {
+    pub tgid: u32,
+    pub protocol: u32,
+}
+
+unsafe impl aya::Pod for NetworkStatsKey {}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Default)]
 pub struct NetworkStats {
@@ -20,17 +95,246 @@ pub struct NetworkStats {
 
 unsafe impl aya::Pod for NetworkStats {}
 
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct Log2Histogram {
+    pub buckets: [u64; HISTOGRAM_BUCKETS],
+    pub sum_us: u64,
+    pub count: u64,
+}
+
+unsafe impl aya::Pod for Log2Histogram {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectStats {
+    pub attempts: u64,
+    pub refused: u64,
+    pub timeout: u64,
+    pub other: u64,
+    pub accepted: u64,
+    pub latency: Log2Histogram,
+}
+
+unsafe impl aya::Pod for ConnectStats {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PendingConnect {
+    pub tgid: u32,
+    pub reset: u32,
+    pub start_ns: u64,
+}
+
+unsafe impl aya::Pod for PendingConnect {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IoRequestKey {
+    pub dev: u32,
+    pub _pad: u32,
+    pub sector: u64,
+}
+
+unsafe impl aya::Pod for IoRequestKey {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PendingIo {
+    pub tgid: u32,
+    pub bytes: u32,
+    pub issued_ns: u64,
+}
+
+unsafe impl aya::Pod for PendingIo {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlockIoKey {
+    pub tgid: u32,
+    /// 内核 dev_t（主设备号 << 20 | 次设备号）
+    pub dev: u32,
+}
+
+unsafe impl aya::Pod for BlockIoKey {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BlockIoStats {
+    pub requests: u64,
+    pub bytes: u64,
+    pub latency: Log2Histogram,
+}
+
+unsafe impl aya::Pod for BlockIoStats {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyscallKey {
+    pub tgid: u32,
+    pub nr: u32,
+}
+
+unsafe impl aya::Pod for SyscallKey {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyscallStats {
+    pub count: u64,
+    pub errors: u64,
+}
+
+unsafe impl aya::Pod for SyscallStats {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreadState {
+    pub tgid: u32,
+    pub _pad: u32,
+    pub since_ns: u64,
+}
+
+unsafe impl aya::Pod for ThreadState {}
+
+/// 进程的调度时延：阻塞到唤醒、唤醒（或被抢占）到重新运行
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SchedStats {
+    pub off_cpu: Log2Histogram,
+    pub run_queue: Log2Histogram,
+}
+
+unsafe impl aya::Pod for SchedStats {}
+
+/// ring buffer 中的生命周期事件
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LifecycleEvent {
+    pub kind: u32,
+    pub pid: u32,
+    pub child_pid: u32,
+    pub comm: [u8; 16],
+}
+
+unsafe impl aya::Pod for LifecycleEvent {}
+
+impl LifecycleEvent {
+    /// 转换为对外的事件，未知类型返回 None
+    fn to_event(self) -> Option<ProcessEvent> {
+        let kind = match self.kind {
+            1 => ProcessEventKind::Exec,
+            2 => ProcessEventKind::Fork,
+            3 => ProcessEventKind::Exit,
+            _ => return None,
+        };
+        let comm_len = self.comm.iter().position(|b| *b == 0).unwrap_or(self.comm.len());
+
+        Some(ProcessEvent {
+            kind,
+            pid: self.pid as i32,
+            child_pid: (kind == ProcessEventKind::Fork).then_some(self.child_pid as i32),
+            comm: String::from_utf8_lossy(&self.comm[..comm_len]).into_owned(),
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        })
+    }
+}
+
+/// 进程的 TCP 统计（eBPF tracepoint）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcpStats {
+    pub retransmits: u64,
+    pub rtt: Option<Log2Histogram>,
+    pub connects: Option<ConnectStats>,
+}
+
+impl From<ConnectStats> for TcpConnectStats {
+    fn from(stats: ConnectStats) -> Self {
+        Self {
+            attempts: stats.attempts,
+            refused: stats.refused,
+            timeout: stats.timeout,
+            other: stats.other,
+            accepted: stats.accepted,
+            latency: (stats.latency.count > 0).then(|| LatencyHistogram::from(stats.latency)),
+        }
+    }
+}
+
+impl From<Log2Histogram> for LatencyHistogram {
+    fn from(stats: Log2Histogram) -> Self {
+        Self {
+            buckets: stats.buckets.to_vec(),
+            sum_us: stats.sum_us,
+            count: stats.count,
+        }
+    }
+}
+
+impl From<NetworkStats> for ProtocolTraffic {
+    fn from(stats: NetworkStats) -> Self {
+        Self {
+            tx_bytes: stats.tx_bytes,
+            rx_bytes: stats.rx_bytes,
+            tx_packets: stats.tx_packets,
+            rx_packets: stats.rx_packets,
+        }
+    }
+}
+
+/// 按对端划分的流量键（IPv4），地址与远端端口为网络字节序，本地端口为主机字节序
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FlowKey {
+    pub tgid: u32,
+    pub protocol: u32,
+    pub remote_addr: u32,
+    pub remote_port: u16,
+    pub local_port: u16,
+}
+
+unsafe impl aya::Pod for FlowKey {}
+
+/// 入口探针暂存的参数
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MsgArgs {
+    pub sk: u64,
+    pub msg: u64,
+    pub depth: u64,
+}
+
+unsafe impl aya::Pod for MsgArgs {}
+
+/// 挂载到某个 cgroup 上的 cgroup_skb 程序
+struct CgroupAttachment {
+    /// cgroup id（cgroupfs 目录的 inode 号）
+    id: u64,
+    /// (程序名, 链接)
+    links: Vec<(&'static str, CgroupSkbLinkId)>,
+}
+
 pub struct EbpfLoader {
     ebpf: Arc<Mutex<Option<Ebpf>>>,
+    /// 已挂载 cgroup_skb 的 cgroup（路径 -> 挂载信息）；与 ebpf 同时加锁时先锁此项
+    cgroups: Mutex<HashMap<String, CgroupAttachment>>,
+    /// 进程生命周期事件（ring buffer 由后台任务读取后广播）
+    events: broadcast::Sender<ProcessEvent>,
 }
 
 impl EbpfLoader {
     pub fn new() -> Self {
         Self {
             ebpf: Arc::new(Mutex::new(None)),
+            cgroups: Mutex::new(HashMap::new()),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
         }
     }
 
+    /// 订阅进程生命周期事件（只含 load 成功之后发生的事件）
+    pub fn subscribe_events(&self) -> broadcast::Receiver<ProcessEvent> {
+        self.events.subscribe()
+    }
+
     pub async fn load(&self) -> anyhow::Result<()> {
         // 使用编译器建议的路径
         #[cfg(debug_assertions)]
@@ -53,35 +357,46 @@ impl EbpfLoader {
             info!("  - {}", name);
         }
 
-        // 附加 tcp_sendmsg kretprobe
-        info!("Attaching tcp_sendmsg kretprobe...");
-        let program: &mut KProbe = ebpf
-            .program_mut("tcp_sendmsg")
-            .ok_or_else(|| anyhow::anyhow!("tcp_sendmsg program not found"))?
-            .try_into()
-            .map_err(|e| anyhow::anyhow!("Failed to convert to KProbe: {:?}", e))?;
-
-        program.load()
-            .map_err(|e| anyhow::anyhow!("Failed to load tcp_sendmsg: {:?}", e))?;
-        
-        program.attach("tcp_sendmsg", 0)
-            .map_err(|e| anyhow::anyhow!("Failed to attach tcp_sendmsg: {:?}", e))?;
-        info!("✓ Attached kretprobe: tcp_sendmsg");
-
-        // 附加 tcp_recvmsg kretprobe
-        info!("Attaching tcp_recvmsg kretprobe...");
-        let program: &mut KProbe = ebpf
-            .program_mut("tcp_recvmsg")
-            .ok_or_else(|| anyhow::anyhow!("tcp_recvmsg program not found"))?
-            .try_into()
-            .map_err(|e| anyhow::anyhow!("Failed to convert to KProbe: {:?}", e))?;
-
-        program.load()
-            .map_err(|e| anyhow::anyhow!("Failed to load tcp_recvmsg: {:?}", e))?;
-        
-        program.attach("tcp_recvmsg", 0)
-            .map_err(|e| anyhow::anyhow!("Failed to attach tcp_recvmsg: {:?}", e))?;
-        info!("✓ Attached kretprobe: tcp_recvmsg");
+        // 附加 kretprobe：TCP 必须成功，UDP 尽力而为
+        for name in REQUIRED_PROBES {
+            attach_kretprobe(&mut ebpf, name)?;
+        }
+        for name in OPTIONAL_PROBES {
+            if let Err(e) = attach_kretprobe(&mut ebpf, name) {
+                warn!("Skipping kretprobe {}: {}", name, e);
+            }
+        }
+
+        // 入口 kprobe 只用于按对端划分流量，失败时总量统计不受影响
+        for name in REQUIRED_PROBES.iter().chain(OPTIONAL_PROBES) {
+            if let Err(e) = attach_kprobe(&mut ebpf, &format!("{}_entry", name), name) {
+                warn!("Skipping kprobe {}_entry, flows will not be tracked: {}", name, e);
+            }
+        }
+
+        // TCP 重传、RTT、建连与块设备 I/O：tracepoint 字段偏移随内核版本变化，从 tracefs 读取
+        attach_tracepoints(&mut ebpf, TRACEPOINTS, 0);
+
+        // 被动建连在 accept() 返回时归属到接受连接的进程
+        if let Err(e) = attach_kretprobe(&mut ebpf, "inet_csk_accept") {
+            warn!("Skipping kretprobe inet_csk_accept, accepted connections will not be counted: {}", e);
+        }
+
+        // 进程 exec / fork / exit：事件写入 ring buffer，由后台任务转发给订阅者
+        let start = offset_start(&[TRACEPOINTS, SYSCALL_TRACEPOINTS, SCHED_TRACEPOINTS]);
+        if attach_tracepoints(&mut ebpf, LIFECYCLE_TRACEPOINTS, start) > 0 {
+            match ebpf.take_map("PROCESS_EVENTS").map(RingBuf::try_from) {
+                Some(Ok(ring_buf)) => spawn_event_reader(ring_buf, self.events.clone()),
+                _ => warn!("PROCESS_EVENTS ring buffer not available, lifecycle events disabled"),
+            }
+        }
+
+        // cgroup_skb 程序先加载，按需挂载到进程独占的 cgroup
+        for (name, _) in CGROUP_SKB_PROGRAMS {
+            if let Err(e) = load_cgroup_skb(&mut ebpf, name) {
+                warn!("Skipping cgroup_skb program {}: {}", name, e);
+            }
+        }
 
         *self.ebpf.lock().await = Some(ebpf);
 
@@ -89,6 +404,32 @@ impl EbpfLoader {
         Ok(())
     }
 
+    /// 挂载系统调用计数程序（在 load 之后调用）
+    pub async fn enable_syscall_tracing(&self) -> anyhow::Result<()> {
+        let mut ebpf_guard = self.ebpf.lock().await;
+        let ebpf = ebpf_guard.as_mut()
+            .ok_or_else(|| anyhow::anyhow!("eBPF not loaded"))?;
+
+        if attach_tracepoints(ebpf, SYSCALL_TRACEPOINTS, offset_start(&[TRACEPOINTS])) == 0 {
+            anyhow::bail!("no raw_syscalls tracepoint attached");
+        }
+        Ok(())
+    }
+
+    /// 挂载调度时延程序（在 load 之后调用）
+    pub async fn enable_sched_tracing(&self) -> anyhow::Result<()> {
+        let mut ebpf_guard = self.ebpf.lock().await;
+        let ebpf = ebpf_guard.as_mut()
+            .ok_or_else(|| anyhow::anyhow!("eBPF not loaded"))?;
+
+        // 两个程序缺一不可：没有 sched_wakeup 就只有被抢占的排队时间
+        let start = offset_start(&[TRACEPOINTS, SYSCALL_TRACEPOINTS]);
+        if attach_tracepoints(ebpf, SCHED_TRACEPOINTS, start) < SCHED_TRACEPOINTS.len() {
+            anyhow::bail!("sched tracepoints not fully attached");
+        }
+        Ok(())
+    }
+
     pub async fn add_pid_to_whitelist(&self, pid: i32) -> anyhow::Result<()> {
         let mut ebpf_guard = self.ebpf.lock().await;
         let ebpf = ebpf_guard.as_mut()
@@ -119,22 +460,240 @@ impl EbpfLoader {
         whitelist.remove(&(pid as u32))
             .map_err(|e| anyhow::anyhow!("Failed to remove PID from whitelist: {:?}", e))?;
 
+        // 清理该进程的系统调用计数
+        if let Some(Ok(mut syscalls)) = ebpf.map_mut("SYSCALLS").map(AyaHashMap::<_, SyscallKey, SyscallStats>::try_from) {
+            let keys: Vec<SyscallKey> = syscalls.keys()
+                .filter_map(|key| key.ok())
+                .filter(|key| key.tgid == pid as u32)
+                .collect();
+            for key in keys {
+                let _ = syscalls.remove(&key);
+            }
+        }
+
+        // 清理以 tgid 为键的 TCP 统计，这些 map 不是 LRU，不清理会一直占用条目
+        let tgid = pid as u32;
+        hash_map_remove::<u32, u64>(ebpf, "TCP_RETRANSMITS", &tgid);
+        hash_map_remove::<u32, Log2Histogram>(ebpf, "TCP_RTT", &tgid);
+        hash_map_remove::<u32, ConnectStats>(ebpf, "TCP_CONNECTS", &tgid);
+
+        // 块设备 I/O 统计按 (tgid, 设备) 分键，逐个删除该进程的条目
+        if let Some(Ok(mut block_io)) = ebpf.map_mut("BLOCK_IO").map(AyaHashMap::<_, BlockIoKey, BlockIoStats>::try_from) {
+            let keys: Vec<BlockIoKey> = block_io.keys()
+                .filter_map(|key| key.ok())
+                .filter(|key| key.tgid == tgid)
+                .collect();
+            for key in keys {
+                let _ = block_io.remove(&key);
+            }
+        }
+
+        hash_map_remove::<u32, SchedStats>(ebpf, "SCHED_LATENCY", &tgid);
+
         info!("✓ Removed PID {} from eBPF whitelist", pid);
         Ok(())
     }
 
-    pub async fn get_network_stats(&self, pid: i32) -> Option<NetworkStats> {
+    /// 进程各协议的网络统计：(协议名, 统计)，没有流量的协议不返回
+    pub async fn get_network_stats(&self, pid: i32) -> Vec<(&'static str, NetworkStats)> {
         let ebpf_guard = self.ebpf.lock().await;
-        let ebpf = ebpf_guard.as_ref()?;
+        let Some(ebpf) = ebpf_guard.as_ref() else {
+            return Vec::new();
+        };
 
-        let network_stats: AyaHashMap<_, u32, NetworkStats> = AyaHashMap::try_from(
-            ebpf.map("NETWORK_STATS")?
-        ).ok()?;
+        let Some(map) = ebpf.map("NETWORK_STATS") else {
+            return Vec::new();
+        };
 
-        network_stats.get(&(pid as u32), 0).ok()
+        let Ok(network_stats) = AyaHashMap::<_, NetworkStatsKey, NetworkStats>::try_from(map) else {
+            return Vec::new();
+        };
+
+        PROTOCOLS
+            .iter()
+            .filter_map(|(protocol, name)| {
+                let key = NetworkStatsKey { tgid: pid as u32, protocol: *protocol };
+                network_stats.get(&key, 0).ok().map(|stats| (*name, stats))
+            })
+            .collect()
     }
 
-    pub async fn get_all_stats(&self) -> Vec<(u32, NetworkStats)> {
+    /// 所有被监控进程的对端流量
+    pub async fn get_flows(&self) -> Vec<(FlowKey, NetworkStats)> {
+        let ebpf_guard = self.ebpf.lock().await;
+        let Some(ebpf) = ebpf_guard.as_ref() else {
+            return Vec::new();
+        };
+
+        let Some(map) = ebpf.map("FLOW_STATS") else {
+            return Vec::new();
+        };
+
+        let Ok(flow_stats) = AyaHashMap::<_, FlowKey, NetworkStats>::try_from(map) else {
+            return Vec::new();
+        };
+
+        flow_stats
+            .iter()
+            .filter_map(|item| item.ok())
+            .collect()
+    }
+
+    /// 进程的 TCP 重传次数、平滑 RTT 分布与建连统计
+    pub async fn get_tcp_stats(&self, pid: i32) -> TcpStats {
+        let ebpf_guard = self.ebpf.lock().await;
+        let Some(ebpf) = ebpf_guard.as_ref() else {
+            return TcpStats::default();
+        };
+
+        let tgid = pid as u32;
+        TcpStats {
+            retransmits: hash_map_get::<u32, u64>(ebpf, "TCP_RETRANSMITS", &tgid).unwrap_or(0),
+            rtt: hash_map_get(ebpf, "TCP_RTT", &tgid),
+            connects: hash_map_get(ebpf, "TCP_CONNECTS", &tgid),
+        }
+    }
+
+    /// 所有被监控进程在各块设备上的 I/O 统计
+    pub async fn get_block_io(&self) -> Vec<(BlockIoKey, BlockIoStats)> {
+        let ebpf_guard = self.ebpf.lock().await;
+        let Some(ebpf) = ebpf_guard.as_ref() else {
+            return Vec::new();
+        };
+
+        let Some(Ok(block_io)) = ebpf.map("BLOCK_IO").map(AyaHashMap::<_, BlockIoKey, BlockIoStats>::try_from) else {
+            return Vec::new();
+        };
+
+        block_io
+            .iter()
+            .filter_map(|item| item.ok())
+            .collect()
+    }
+
+    /// 进程的阻塞时间与运行队列等待时间分布
+    pub async fn get_sched_stats(&self, pid: i32) -> Option<SchedStats> {
+        let ebpf_guard = self.ebpf.lock().await;
+        hash_map_get(ebpf_guard.as_ref()?, "SCHED_LATENCY", &(pid as u32))
+    }
+
+    /// 所有被监控进程按系统调用号的调用与错误次数
+    pub async fn get_syscalls(&self) -> Vec<(SyscallKey, SyscallStats)> {
+        let ebpf_guard = self.ebpf.lock().await;
+        let Some(ebpf) = ebpf_guard.as_ref() else {
+            return Vec::new();
+        };
+
+        let Some(Ok(syscalls)) = ebpf.map("SYSCALLS").map(AyaHashMap::<_, SyscallKey, SyscallStats>::try_from) else {
+            return Vec::new();
+        };
+
+        syscalls
+            .iter()
+            .filter_map(|item| item.ok())
+            .collect()
+    }
+
+    /// 同步注册进程的进程名，exec 后进程名命中的新进程才会上报事件
+    pub async fn set_exec_comms(&self, comms: &HashSet<String>) {
+        let mut ebpf_guard = self.ebpf.lock().await;
+        let Some(ebpf) = ebpf_guard.as_mut() else {
+            return;
+        };
+        let Some(Ok(mut map)) = ebpf.map_mut("EXEC_COMMS").map(AyaHashMap::<_, [u8; 16], u8>::try_from) else {
+            return;
+        };
+
+        let wanted: HashSet<[u8; 16]> = comms.iter().map(|comm| comm_key(comm)).collect();
+        let stale: Vec<[u8; 16]> = map.keys()
+            .filter_map(|key| key.ok())
+            .filter(|key| !wanted.contains(key))
+            .collect();
+        for key in stale {
+            let _ = map.remove(&key);
+        }
+        for key in &wanted {
+            if map.get(key, 0).is_err() {
+                let _ = map.insert(key, 1, 0);
+            }
+        }
+    }
+
+    /// 在 cgroup 上挂载 cgroup_skb 程序（已挂载时直接返回），返回 cgroup id
+    pub async fn attach_cgroup(&self, cgroup: &str) -> anyhow::Result<u64> {
+        let mut cgroups = self.cgroups.lock().await;
+        if let Some(attachment) = cgroups.get(cgroup) {
+            return Ok(attachment.id);
+        }
+
+        let mut ebpf_guard = self.ebpf.lock().await;
+        let ebpf = ebpf_guard.as_mut()
+            .ok_or_else(|| anyhow::anyhow!("eBPF not loaded"))?;
+
+        let dir = File::open(cgroup_dir(cgroup))
+            .map_err(|e| anyhow::anyhow!("Failed to open cgroup {}: {}", cgroup, e))?;
+        let id = dir.metadata()?.ino();
+
+        let mut links = Vec::new();
+        for (name, attach_type) in CGROUP_SKB_PROGRAMS {
+            match cgroup_skb_program(ebpf, name).and_then(|program| {
+                program.attach(&dir, *attach_type, CgroupAttachMode::AllowMultiple)
+                    .map_err(|e| anyhow::anyhow!("Failed to attach {}: {:?}", name, e))
+            }) {
+                Ok(link) => links.push((*name, link)),
+                Err(e) => {
+                    detach_cgroup_skb(ebpf, links);
+                    return Err(e);
+                }
+            }
+        }
+
+        info!("✓ Attached cgroup_skb programs to cgroup {} (id {})", cgroup, id);
+        cgroups.insert(cgroup.to_string(), CgroupAttachment { id, links });
+        Ok(id)
+    }
+
+    /// 卸载不再使用的 cgroup 上的 cgroup_skb 程序，并清理其统计
+    pub async fn retain_cgroups(&self, in_use: &HashSet<&str>) {
+        let mut cgroups = self.cgroups.lock().await;
+        let unused: Vec<String> = cgroups.keys()
+            .filter(|cgroup| !in_use.contains(cgroup.as_str()))
+            .cloned()
+            .collect();
+        if unused.is_empty() {
+            return;
+        }
+
+        let mut ebpf_guard = self.ebpf.lock().await;
+        let Some(ebpf) = ebpf_guard.as_mut() else {
+            return;
+        };
+
+        for cgroup in unused {
+            let Some(attachment) = cgroups.remove(&cgroup) else {
+                continue;
+            };
+            detach_cgroup_skb(ebpf, attachment.links);
+
+            if let Some(map) = ebpf.map_mut("CGROUP_STATS") {
+                if let Ok(mut stats) = AyaHashMap::<_, u64, NetworkStats>::try_from(map) {
+                    let _ = stats.remove(&attachment.id);
+                }
+            }
+            info!("✓ Detached cgroup_skb programs from cgroup {}", cgroup);
+        }
+    }
+
+    /// cgroup 的 skb 统计，尚无流量时为全 0
+    pub async fn get_cgroup_stats(&self, id: u64) -> Option<NetworkStats> {
+        let ebpf_guard = self.ebpf.lock().await;
+        let ebpf = ebpf_guard.as_ref()?;
+        let stats = AyaHashMap::<_, u64, NetworkStats>::try_from(ebpf.map("CGROUP_STATS")?).ok()?;
+        Some(stats.get(&id, 0).unwrap_or_default())
+    }
+
+    #[allow(dead_code)]
+    pub async fn get_all_stats(&self) -> Vec<(NetworkStatsKey, NetworkStats)> {
         let ebpf_guard = self.ebpf.lock().await;
         let Some(ebpf) = ebpf_guard.as_ref() else {
             return Vec::new();
@@ -144,7 +703,7 @@ impl EbpfLoader {
             return Vec::new();
         };
 
-        let Ok(network_stats) = AyaHashMap::<_, u32, NetworkStats>::try_from(map) else {
+        let Ok(network_stats) = AyaHashMap::<_, NetworkStatsKey, NetworkStats>::try_from(map) else {
             return Vec::new();
         };
 
@@ -155,8 +714,284 @@ impl EbpfLoader {
     }
 }
 
+impl EbpfLoader {
+    /// eBPF 程序是否已加载
+    pub async fn is_loaded(&self) -> bool {
+        self.ebpf.lock().await.is_some()
+    }
+
+    /// 各 eBPF map 的使用情况：(map 名称, 当前条目数, 容量)
+    pub async fn map_usage(&self) -> Vec<(&'static str, usize, u32)> {
+        let ebpf_guard = self.ebpf.lock().await;
+        let Some(ebpf) = ebpf_guard.as_ref() else {
+            return Vec::new();
+        };
+
+        [
+            ("NETWORK_STATS", hash_map_usage::<NetworkStatsKey, NetworkStats>(ebpf, "NETWORK_STATS")),
+            ("PID_WHITELIST", hash_map_usage::<u32, u8>(ebpf, "PID_WHITELIST")),
+            ("FLOW_STATS", hash_map_usage::<FlowKey, NetworkStats>(ebpf, "FLOW_STATS")),
+            ("MSG_ARGS", hash_map_usage::<u64, MsgArgs>(ebpf, "MSG_ARGS")),
+            ("CGROUP_STATS", hash_map_usage::<u64, NetworkStats>(ebpf, "CGROUP_STATS")),
+            ("SOCK_OWNER", hash_map_usage::<u64, u32>(ebpf, "SOCK_OWNER")),
+            ("TCP_RETRANSMITS", hash_map_usage::<u32, u64>(ebpf, "TCP_RETRANSMITS")),
+            ("TCP_RTT", hash_map_usage::<u32, Log2Histogram>(ebpf, "TCP_RTT")),
+            ("TCP_CONNECTS", hash_map_usage::<u32, ConnectStats>(ebpf, "TCP_CONNECTS")),
+            ("PENDING_CONNECTS", hash_map_usage::<u64, PendingConnect>(ebpf, "PENDING_CONNECTS")),
+            ("PENDING_IO", hash_map_usage::<IoRequestKey, PendingIo>(ebpf, "PENDING_IO")),
+            ("BLOCK_IO", hash_map_usage::<BlockIoKey, BlockIoStats>(ebpf, "BLOCK_IO")),
+            ("SYSCALLS", hash_map_usage::<SyscallKey, SyscallStats>(ebpf, "SYSCALLS")),
+            ("THREAD_OFF_CPU", hash_map_usage::<u32, ThreadState>(ebpf, "THREAD_OFF_CPU")),
+            ("THREAD_RUNNABLE", hash_map_usage::<u32, ThreadState>(ebpf, "THREAD_RUNNABLE")),
+            ("SCHED_LATENCY", hash_map_usage::<u32, SchedStats>(ebpf, "SCHED_LATENCY")),
+            ("EXEC_COMMS", hash_map_usage::<[u8; 16], u8>(ebpf, "EXEC_COMMS")),
+            ("EXEC_CHILDREN", hash_map_usage::<u32, u8>(ebpf, "EXEC_CHILDREN")),
+        ]
+            .into_iter()
+            .filter_map(|(name, usage)| usage.map(|(entries, capacity)| (name, entries, capacity)))
+            .collect()
+    }
+}
+
+/// 加载并挂载与内核函数同名的 kretprobe
+fn attach_kretprobe(ebpf: &mut Ebpf, name: &str) -> anyhow::Result<()> {
+    info!("Attaching {} kretprobe...", name);
+    let program: &mut KProbe = ebpf
+        .program_mut(name)
+        .ok_or_else(|| anyhow::anyhow!("{} program not found", name))?
+        .try_into()
+        .map_err(|e| anyhow::anyhow!("Failed to convert to KProbe: {:?}", e))?;
+
+    program.load()
+        .map_err(|e| anyhow::anyhow!("Failed to load {}: {:?}", name, e))?;
+
+    program.attach(name, 0)
+        .map_err(|e| anyhow::anyhow!("Failed to attach {}: {:?}", name, e))?;
+    info!("✓ Attached kretprobe: {}", name);
+    Ok(())
+}
+
+/// 加载程序 `program` 并以 kprobe 挂载到内核函数 `function`
+fn attach_kprobe(ebpf: &mut Ebpf, program: &str, function: &str) -> anyhow::Result<()> {
+    info!("Attaching {} kprobe...", program);
+    let kprobe: &mut KProbe = ebpf
+        .program_mut(program)
+        .ok_or_else(|| anyhow::anyhow!("{} program not found", program))?
+        .try_into()
+        .map_err(|e| anyhow::anyhow!("Failed to convert to KProbe: {:?}", e))?;
+
+    kprobe.load()
+        .map_err(|e| anyhow::anyhow!("Failed to load {}: {:?}", program, e))?;
+
+    kprobe.attach(function, 0)
+        .map_err(|e| anyhow::anyhow!("Failed to attach {}: {:?}", program, e))?;
+    info!("✓ Attached kprobe: {}", program);
+    Ok(())
+}
+
+/// 后台读取生命周期事件 ring buffer 并广播
+fn spawn_event_reader(ring_buf: RingBuf<MapData>, events: broadcast::Sender<ProcessEvent>) {
+    tokio::spawn(async move {
+        let mut ring_buf = match AsyncFd::new(ring_buf) {
+            Ok(fd) => fd,
+            Err(e) => {
+                warn!("Failed to poll PROCESS_EVENTS ring buffer: {}", e);
+                return;
+            }
+        };
+        info!("✓ Lifecycle event reader started");
+
+        loop {
+            let mut guard = match ring_buf.readable_mut().await {
+                Ok(guard) => guard,
+                Err(e) => {
+                    warn!("Lifecycle event reader stopped: {}", e);
+                    return;
+                }
+            };
+
+            let reader = guard.get_inner_mut();
+            while let Some(item) = reader.next() {
+                if item.len() < std::mem::size_of::<LifecycleEvent>() {
+                    continue;
+                }
+                let raw = unsafe { std::ptr::read_unaligned(item.as_ptr() as *const LifecycleEvent) };
+                // 没有订阅者时发送失败，直接丢弃
+                if let Some(event) = raw.to_event() {
+                    let _ = events.send(event);
+                }
+            }
+            guard.clear_ready();
+        }
+    });
+}
+
+/// 内核中的进程名：最多 15 字节，以 NUL 结尾（TASK_COMM_LEN = 16）
+fn comm_key(comm: &str) -> [u8; 16] {
+    let mut key = [0u8; 16];
+    let bytes = comm.as_bytes();
+    let len = bytes.len().min(15);
+    key[..len].copy_from_slice(&bytes[..len]);
+    key
+}
+
+/// 排在给定 tracepoint 列表之后的偏移起始下标
+fn offset_start(preceding: &[&[(&str, &str, &[&str])]]) -> u32 {
+    preceding.iter()
+        .flat_map(|tracepoints| tracepoints.iter())
+        .map(|(_, _, fields)| fields.len() as u32)
+        .sum()
+}
+
+/// 依次写入字段偏移并挂载 tracepoint，失败的只告警跳过；返回成功挂载的数量
+fn attach_tracepoints(ebpf: &mut Ebpf, tracepoints: &[(&str, &str, &[&str])], start: u32) -> usize {
+    let mut index = start;
+    let mut attached = 0;
+    for (category, name, fields) in tracepoints {
+        let result = write_tracepoint_offsets(ebpf, category, name, fields, index)
+            .and_then(|_| attach_tracepoint(ebpf, category, name));
+        match result {
+            Ok(()) => attached += 1,
+            Err(e) => warn!("Skipping tracepoint {}:{}: {}", category, name, e),
+        }
+        index += fields.len() as u32;
+    }
+    attached
+}
+
+/// 加载并挂载 tracepoint 程序（程序名与 tracepoint 名相同）
+fn attach_tracepoint(ebpf: &mut Ebpf, category: &str, name: &str) -> anyhow::Result<()> {
+    info!("Attaching {}:{} tracepoint...", category, name);
+    let program: &mut TracePoint = ebpf
+        .program_mut(name)
+        .ok_or_else(|| anyhow::anyhow!("{} program not found", name))?
+        .try_into()
+        .map_err(|e| anyhow::anyhow!("Failed to convert to TracePoint: {:?}", e))?;
+
+    program.load()
+        .map_err(|e| anyhow::anyhow!("Failed to load {}: {:?}", name, e))?;
+
+    program.attach(category, name)
+        .map_err(|e| anyhow::anyhow!("Failed to attach {}: {:?}", name, e))?;
+    info!("✓ Attached tracepoint: {}:{}", category, name);
+    Ok(())
+}
+
+/// 从 tracefs 读取 tracepoint 字段偏移，从下标 `start` 起写入 TRACEPOINT_OFFSETS
+fn write_tracepoint_offsets(ebpf: &mut Ebpf, category: &str, name: &str, fields: &[&str], start: u32) -> anyhow::Result<()> {
+    let format = TRACEFS_ROOTS
+        .iter()
+        .find_map(|root| std::fs::read_to_string(format!("{}/events/{}/{}/format", root, category, name)).ok())
+        .ok_or_else(|| anyhow::anyhow!("tracepoint not found in tracefs"))?;
+
+    let mut offsets: Array<_, u32> = Array::try_from(
+        ebpf.map_mut("TRACEPOINT_OFFSETS")
+            .ok_or_else(|| anyhow::anyhow!("TRACEPOINT_OFFSETS map not found"))?
+    ).map_err(|e| anyhow::anyhow!("Failed to get offsets map: {:?}", e))?;
+
+    for (index, field) in (start..).zip(fields.iter()) {
+        let offset = field_offset(&format, field)
+            .ok_or_else(|| anyhow::anyhow!("field '{}' not found", field))?;
+        offsets.set(index, offset, 0)
+            .map_err(|e| anyhow::anyhow!("Failed to set offset of {}: {:?}", field, e))?;
+    }
+    Ok(())
+}
+
+/// 解析 tracepoint format 中字段的偏移，如 `field:const void * skaddr; offset:16; size:8; signed:0;`
+fn field_offset(format: &str, field: &str) -> Option<u32> {
+    format.lines().find_map(|line| {
+        let mut parts = line.trim().split(';').map(str::trim);
+        let declaration = parts.next()?.strip_prefix("field:")?;
+        // 去掉数组维度后取最后一个标识符
+        let name = declaration.split('[').next()?.rsplit([' ', '*']).next()?;
+        if name != field {
+            return None;
+        }
+        parts.find_map(|part| part.strip_prefix("offset:"))?.parse().ok()
+    })
+}
+
+/// 加载 cgroup_skb 程序，挂载在 attach_cgroup 中按需进行
+fn load_cgroup_skb(ebpf: &mut Ebpf, name: &str) -> anyhow::Result<()> {
+    cgroup_skb_program(ebpf, name)?
+        .load()
+        .map_err(|e| anyhow::anyhow!("Failed to load {}: {:?}", name, e))?;
+    info!("✓ Loaded cgroup_skb program: {}", name);
+    Ok(())
+}
+
+fn cgroup_skb_program<'a>(ebpf: &'a mut Ebpf, name: &str) -> anyhow::Result<&'a mut CgroupSkb> {
+    ebpf.program_mut(name)
+        .ok_or_else(|| anyhow::anyhow!("{} program not found", name))?
+        .try_into()
+        .map_err(|e| anyhow::anyhow!("Failed to convert to CgroupSkb: {:?}", e))
+}
+
+fn detach_cgroup_skb(ebpf: &mut Ebpf, links: Vec<(&'static str, CgroupSkbLinkId)>) {
+    for (name, link) in links {
+        if let Err(e) = cgroup_skb_program(ebpf, name).and_then(|program| {
+            program.detach(link).map_err(|e| anyhow::anyhow!("{:?}", e))
+        }) {
+            warn!("Failed to detach {}: {}", name, e);
+        }
+    }
+}
+
+/// 读取 HashMap 中的单个条目
+fn hash_map_get<K: Pod, V: Pod>(ebpf: &Ebpf, name: &str, key: &K) -> Option<V> {
+    let map: AyaHashMap<_, K, V> = AyaHashMap::try_from(ebpf.map(name)?).ok()?;
+    map.get(key, 0).ok()
+}
+
+/// 从 HashMap 删除一个键，map 不存在或键不存在时忽略
+fn hash_map_remove<K: Pod, V: Pod>(ebpf: &mut Ebpf, name: &str, key: &K) {
+    if let Some(Ok(mut map)) = ebpf.map_mut(name).map(AyaHashMap::<_, K, V>::try_from) {
+        let _ = map.remove(key);
+    }
+}
+
+/// 统计 HashMap 当前条目数与容量
+fn hash_map_usage<K: Pod, V: Pod>(ebpf: &Ebpf, name: &str) -> Option<(usize, u32)> {
+    let map: AyaHashMap<_, K, V> = AyaHashMap::try_from(ebpf.map(name)?).ok()?;
+    let capacity = map.map().info().ok()?.max_entries();
+    let entries = map.keys().filter_map(|key| key.ok()).count();
+    Some((entries, capacity))
+}
+
 impl Default for EbpfLoader {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TCP_PROBE_FORMAT: &str = "name: tcp_probe
+ID: 1432
+format:
+\tfield:unsigned short common_type;\toffset:0;\tsize:2;\tsigned:0;
+\tfield:__u8 saddr[sizeof(struct sockaddr_in6)];\toffset:8;\tsize:28;\tsigned:0;
+\tfield:__u32 srtt;\toffset:100;\tsize:4;\tsigned:0;
+\tfield:const void * skaddr;\toffset:128;\tsize:8;\tsigned:0;
+";
+
+    #[test]
+    fn test_field_offset() {
+        assert_eq!(field_offset(TCP_PROBE_FORMAT, "srtt"), Some(100));
+        assert_eq!(field_offset(TCP_PROBE_FORMAT, "skaddr"), Some(128));
+        assert_eq!(field_offset(TCP_PROBE_FORMAT, "saddr"), Some(8));
+        assert_eq!(field_offset(TCP_PROBE_FORMAT, "snd_cwnd"), None);
+    }
+
+    #[test]
+    fn test_comm_key() {
+        assert_eq!(&comm_key("nginx")[..6], b"nginx\0");
+        // 内核截断为 15 字节并保留结尾 NUL
+        let key = comm_key("kafka-server-start");
+        assert_eq!(&key[..15], b"kafka-server-st");
+        assert_eq!(key[15], 0);
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+
+use crate::models::FlowStats;
+use crate::services::ebpf_loader::{FlowKey, NetworkStats, PROTOCOLS};
+
+/// 每个进程在状态中保留的对端数量上限
+pub const MAX_FLOWS_PER_PROCESS: usize = 100;
+
+/// 将 eBPF 的流量条目按 PID 分组，每个进程按总字节数降序保留前 `limit` 条
+pub fn flows_by_pid<I>(raw: I, limit: usize) -> HashMap<u32, Vec<FlowStats>>
+where
+    I: IntoIterator<Item = (FlowKey, NetworkStats)>,
+{
+    let mut grouped: HashMap<u32, Vec<FlowStats>> = HashMap::new();
+    for (key, stats) in raw {
+        grouped.entry(key.tgid).or_default().push(flow_stats(&key, &stats));
+    }
+
+    for flows in grouped.values_mut() {
+        sort_flows(flows);
+        flows.truncate(limit);
+    }
+    grouped
+}
+
+/// 按总字节数降序排列，字节数相同时按对端排序保证输出稳定
+pub fn sort_flows(flows: &mut [FlowStats]) {
+    flows.sort_by(|a, b| {
+        b.total_bytes().cmp(&a.total_bytes())
+            .then_with(|| a.remote_address.cmp(&b.remote_address))
+            .then_with(|| a.remote_port.cmp(&b.remote_port))
+            .then_with(|| a.local_port.cmp(&b.local_port))
+            .then_with(|| a.protocol.cmp(&b.protocol))
+    });
+}
+
+fn flow_stats(key: &FlowKey, stats: &NetworkStats) -> FlowStats {
+    let protocol = PROTOCOLS
+        .iter()
+        .find(|(number, _)| *number == key.protocol)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| key.protocol.to_string());
+
+    FlowStats {
+        protocol,
+        // 内核按网络字节序存放，按原始字节顺序解释即可
+        remote_address: Ipv4Addr::from(key.remote_addr.to_ne_bytes()).to_string(),
+        remote_port: u16::from_be(key.remote_port),
+        local_port: key.local_port,
+        tx_bytes: stats.tx_bytes,
+        rx_bytes: stats.rx_bytes,
+        tx_packets: stats.tx_packets,
+        rx_packets: stats.rx_packets,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(tgid: u32, addr: [u8; 4], port: u16, tx_bytes: u64) -> (FlowKey, NetworkStats) {
+        let key = FlowKey {
+            tgid,
+            protocol: 6,
+            remote_addr: u32::from_ne_bytes(addr),
+            remote_port: port.to_be(),
+            local_port: 40000,
+        };
+        (key, NetworkStats { tx_bytes, tx_packets: 1, ..NetworkStats::default() })
+    }
+
+    #[test]
+    fn test_flows_by_pid() {
+        let raw = vec![
+            entry(1, [10, 0, 0, 1], 5432, 100),
+            entry(1, [10, 0, 0, 2], 6379, 300),
+            entry(1, [10, 0, 0, 3], 443, 200),
+            entry(2, [192, 168, 1, 1], 53, 10),
+        ];
+
+        let grouped = flows_by_pid(raw, 2);
+        let top: Vec<(&str, u16)> = grouped[&1].iter().map(|f| (f.remote_address.as_str(), f.remote_port)).collect();
+        assert_eq!(top, vec![("10.0.0.2", 6379), ("10.0.0.3", 443)]);
+        assert_eq!(grouped[&2][0].protocol, "tcp");
+        assert_eq!(grouped[&2][0].local_port, 40000);
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::models::{HistoryPoint, HistorySample, ProcessHistory, ProcessStats};
+use crate::state::AppState;
+
+/// 读取计数器字段
+type CounterFn = fn(&ProcessStats) -> f64;
+
+/// 计算速率的计数器字段
+const COUNTERS: &[(&str, CounterFn)] = &[
+    ("cpu_time_seconds", |s| s.cpu_time_seconds),
+    ("disk_read_bytes", |s| s.disk_read_bytes as f64),
+    ("disk_written_bytes", |s| s.disk_written_bytes as f64),
+    ("network_tx_bytes", |s| s.network_tx_bytes as f64),
+    ("network_rx_bytes", |s| s.network_rx_bytes as f64),
+    ("network_tx_packets", |s| s.network_tx_packets as f64),
+    ("network_rx_packets", |s| s.network_rx_packets as f64),
+];
+
+/// 按 step 降采样（每个区间取最后一个采样点），并计算相邻点之间的计数器速率
+///
+/// step 为 0 时保留全部采样点
+pub fn build_points<'a, I>(samples: I, since: u64, step: u64) -> Vec<HistoryPoint>
+where
+    I: IntoIterator<Item = &'a HistorySample>,
+{
+    let mut selected: Vec<&HistorySample> = Vec::new();
+    for sample in samples {
+        let same_bucket = step > 0
+            && selected.last().is_some_and(|last| {
+                (last.timestamp.saturating_sub(since)) / step == (sample.timestamp.saturating_sub(since)) / step
+            });
+        if same_bucket {
+            *selected.last_mut().unwrap() = sample;
+        } else {
+            selected.push(sample);
+        }
+    }
+
+    let mut points = Vec::with_capacity(selected.len());
+    let mut previous: Option<&HistorySample> = None;
+    for sample in selected {
+        let mut rates = BTreeMap::new();
+        if let Some(prev) = previous {
+            let elapsed = sample.timestamp.saturating_sub(prev.timestamp) as f64;
+            // PID 变化或进程未运行时计数器不连续
+            let continuous = elapsed > 0.0 && sample.is_running && prev.is_running && sample.pid == prev.pid;
+            if continuous {
+                for (name, value) in COUNTERS {
+                    let delta = value(&sample.stats) - value(&prev.stats);
+                    if delta >= 0.0 {
+                        rates.insert(*name, delta / elapsed);
+                    }
+                }
+            }
+        }
+
+        points.push(HistoryPoint { sample: sample.clone(), rates });
+        previous = Some(sample);
+    }
+    points
+}
+
+/// 启动后台历史采样任务：按固定间隔记录一次后台采集任务刷新后的状态
+pub fn spawn_history_recorder(data: AppState, interval: Duration, capacity: usize) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+
+            let mut state = data.lock().unwrap();
+            let state = &mut *state;
+
+            // 清理已注销进程的历史
+            state.history.retain(|name, _| state.processes.contains_key(name));
+
+            for (name, status) in state.processes.iter() {
+                state.history
+                    .entry(name.clone())
+                    .or_insert_with(|| ProcessHistory::new(capacity))
+                    .push(HistorySample {
+                        timestamp: status.last_check,
+                        is_running: status.is_running,
+                        pid: status.pid,
+                        stats: status.stats.clone(),
+                    });
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp: u64, pid: i32, disk_read_bytes: u64) -> HistorySample {
+        HistorySample {
+            timestamp,
+            is_running: true,
+            pid: Some(pid),
+            stats: ProcessStats { disk_read_bytes, ..ProcessStats::default() },
+        }
+    }
+
+    #[test]
+    fn test_build_points() {
+        let samples = [
+            sample(100, 1, 0),
+            sample(110, 1, 500),
+            sample(120, 1, 1000),
+            sample(130, 2, 10),
+        ];
+
+        // step=20：[100,120) 取 110，[120,140) 取 130
+        let points = build_points(&samples, 100, 20);
+        assert_eq!(points.iter().map(|p| p.sample.timestamp).collect::<Vec<_>>(), vec![110, 130]);
+        // PID 变化，不计算速率
+        assert!(points[1].rates.is_empty());
+
+        let points = build_points(&samples, 100, 0);
+        assert!(points[0].rates.is_empty());
+        assert_eq!(points[1].rates["disk_read_bytes"], 50.0);
+        assert_eq!(points[2].rates["disk_read_bytes"], 50.0);
+    }
+
+    #[test]
+    fn test_history_skips_duplicate_timestamps() {
+        let mut history = ProcessHistory::new(10);
+        history.push(sample(100, 1, 0));
+        history.push(sample(100, 1, 0));
+        history.push(sample(110, 1, 500));
+
+        let timestamps: Vec<u64> = history.since(0).map(|s| s.timestamp).collect();
+        assert_eq!(timestamps, vec![100, 110]);
+    }
+}
@@ -0,0 +1,171 @@
+use regex::Regex;
+use std::collections::HashMap;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::models::{NamedProcessEvent, ProcessEvent, ProcessEventKind, ProcessStatus};
+use crate::state::AppState;
+
+/// 读取进程命令行（参数以空格连接，与 sysinfo 匹配时一致）
+pub fn read_cmdline(pid: i32) -> Option<String> {
+    let raw = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let args: Vec<String> = raw
+        .split(|b| *b == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    Some(args.join(" "))
+}
+
+/// 读取进程名（/proc/<pid>/comm）
+pub fn read_comm(pid: i32) -> Option<String> {
+    let comm = std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    Some(comm.trim_end_matches('\n').to_string())
+}
+
+/// fork 产生的任务是否为线程；任务已退出无法判断时返回 None
+pub fn is_thread(pid: i32) -> Option<bool> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    parse_tgid(&status).map(|tgid| tgid != pid)
+}
+
+fn parse_tgid(status: &str) -> Option<i32> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Tgid:"))
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// 注册命令行模式编译后的正则缓存，无效模式记为 None
+#[derive(Default)]
+pub struct PatternCache {
+    regexes: HashMap<String, Option<Regex>>,
+}
+
+impl PatternCache {
+    /// 命令行是否匹配注册的模式：优先按正则匹配，模式无效时按子串匹配命令行或进程名
+    pub fn matches(&mut self, pattern: &str, cmdline: &str, name: &str) -> bool {
+        let regex = self.regexes
+            .entry(pattern.to_string())
+            .or_insert_with(|| Regex::new(pattern).ok());
+        match regex {
+            Some(regex) => regex.is_match(cmdline),
+            None => cmdline.contains(pattern) || name.contains(pattern),
+        }
+    }
+
+    /// 丢弃已不属于任何注册进程的模式
+    fn retain(&mut self, processes: &HashMap<String, ProcessStatus>) {
+        if self.regexes.len() > processes.len() {
+            self.regexes.retain(|pattern, _| processes.values().any(|status| &status.config.cmdline == pattern));
+        }
+    }
+}
+
+/// 事件所属的注册进程名
+///
+/// fork / exit 按当前 PID 归属；exec 还会按命令行匹配，以便捕获重启后尚未刷新的新进程
+pub fn attribute_event<'a>(
+    processes: &'a HashMap<String, ProcessStatus>,
+    event: &ProcessEvent,
+    cmdline: Option<&str>,
+    patterns: &mut PatternCache,
+) -> Option<&'a str> {
+    if let Some((name, _)) = processes.iter().find(|(_, status)| status.pid == Some(event.pid)) {
+        return Some(name);
+    }
+    if event.kind != ProcessEventKind::Exec {
+        return None;
+    }
+
+    let cmdline = cmdline?;
+    processes
+        .iter()
+        .find(|(_, status)| patterns.matches(&status.config.cmdline, cmdline, &event.comm))
+        .map(|(name, _)| name.as_str())
+}
+
+/// 启动后台归属任务：每个事件只读取一次 /proc 并归属一次，再广播给所有 SSE 订阅者
+pub fn spawn_event_attributor(data: AppState) {
+    let (mut receiver, events) = {
+        let state = data.lock().unwrap();
+        (state.ebpf_loader.subscribe_events(), state.events.clone())
+    };
+
+    tokio::spawn(async move {
+        let mut patterns = PatternCache::default();
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    log::warn!("Lifecycle event attribution lagged, {} events dropped", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+
+            // 没有订阅者时不做归属
+            if events.receiver_count() == 0 {
+                continue;
+            }
+
+            // 线程创建不算 fork
+            if let (ProcessEventKind::Fork, Some(child)) = (event.kind, event.child_pid) {
+                if is_thread(child) == Some(true) {
+                    continue;
+                }
+            }
+
+            // /proc 在加锁前读取
+            let cmdline = (event.kind == ProcessEventKind::Exec).then(|| read_cmdline(event.pid)).flatten();
+            let name = {
+                let state = data.lock().unwrap();
+                patterns.retain(&state.processes);
+                attribute_event(&state.processes, &event, cmdline.as_deref(), &mut patterns).map(str::to_string)
+            };
+            if let Some(name) = name {
+                let _ = events.send(NamedProcessEvent { name, event });
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProcessConfig;
+
+    fn status(cmdline: &str, pid: i32) -> ProcessStatus {
+        let mut status = ProcessStatus::new(ProcessConfig::new("", cmdline));
+        status.pid = Some(pid);
+        status
+    }
+
+    fn event(kind: ProcessEventKind, pid: i32) -> ProcessEvent {
+        ProcessEvent { kind, pid, child_pid: None, comm: "nginx".to_string(), timestamp: 0 }
+    }
+
+    #[test]
+    fn test_attribute_event() {
+        let processes = HashMap::from([("nginx".to_string(), status("nginx: master", 100))]);
+        let mut patterns = PatternCache::default();
+
+        assert_eq!(attribute_event(&processes, &event(ProcessEventKind::Exit, 100), None, &mut patterns), Some("nginx"));
+        assert_eq!(attribute_event(&processes, &event(ProcessEventKind::Exit, 200), None, &mut patterns), None);
+        // 重启后的新进程按命令行匹配
+        let exec = event(ProcessEventKind::Exec, 200);
+        assert_eq!(attribute_event(&processes, &exec, Some("nginx: master process"), &mut patterns), Some("nginx"));
+        assert_eq!(attribute_event(&processes, &exec, Some("/usr/bin/redis-server"), &mut patterns), None);
+        assert_eq!(patterns.regexes.len(), 1);
+
+        // 注销后缓存的正则随之清理
+        patterns.retain(&HashMap::new());
+        assert!(patterns.regexes.is_empty());
+    }
+
+    #[test]
+    fn test_parse_tgid() {
+        let status = "Name:\tjava\nUmask:\t0022\nState:\tS (sleeping)\nTgid:\t4242\nNgid:\t0\nPid:\t4250\n";
+        assert_eq!(parse_tgid(status), Some(4242));
+        assert_eq!(parse_tgid("Name:\tjava\n"), None);
+    }
+}
@@ -1,6 +1,27 @@
 pub mod process_checker;
 pub mod stats_collector;
 pub mod ebpf_loader;
+pub mod socket_inventory;
+pub mod prober;
+pub mod remote_write;
+pub mod pushgateway;
+pub mod sinks;
+pub mod history;
+pub mod flows;
+pub mod cgroup;
+pub mod block;
+pub mod syscalls;
+pub mod lifecycle;
 
 pub use process_checker::{check_process_running, get_process_pid, get_all_matching_pids};
-pub use stats_collector::StatsCollector;
\ No newline at end of file
+pub use stats_collector::StatsCollector;
+pub use socket_inventory::{collect_socket_inventory, ConnectionKey};
+pub use prober::{spawn_prober, validate_probes};
+pub use remote_write::{spawn_remote_writer, RemoteWriteConfig, RemoteWriter};
+pub use pushgateway::{spawn_pushgateway, Pushgateway, PushgatewayConfig};
+pub use history::{build_points, spawn_history_recorder};
+pub use flows::{flows_by_pid, MAX_FLOWS_PER_PROCESS};
+pub use cgroup::owned_cgroup;
+pub use block::block_io_by_pid;
+pub use syscalls::{sort_by_error_rate, syscalls_by_pid};
+pub use sinks::{spawn_sinks, GraphiteSink, InfluxSink, OtlpConfig, OtlpExporter, Sink, SinkFormat, SinkSpec};
@@ -0,0 +1,191 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tokio::net::TcpStream;
+use tokio::process::Command;
+use tokio::task::JoinSet;
+use tokio::time::timeout;
+
+use crate::models::{ProbeConfig, ProbeKind, ProbeResult};
+use crate::state::AppState;
+
+/// 校验注册请求中的探测：exec 探测需显式开启，同一注册内探测名称不能重复（结果按名称索引）
+pub fn validate_probes(probes: &[ProbeConfig], allow_exec: bool) -> Result<(), String> {
+    let mut labels = HashSet::new();
+    for probe in probes {
+        if matches!(probe.kind, ProbeKind::Exec { .. }) && !allow_exec {
+            return Err(format!("exec probe '{}' is not allowed, start the exporter with --allow-exec-probes", probe.label()));
+        }
+        if !labels.insert(probe.label()) {
+            return Err(format!("duplicate probe name '{}'", probe.label()));
+        }
+    }
+    Ok(())
+}
+
+/// 执行单个探测
+pub async fn run_probe(probe: &ProbeConfig) -> ProbeResult {
+    let limit = Duration::from_millis(probe.timeout_ms);
+    let started = Instant::now();
+
+    let outcome = match timeout(limit, execute(&probe.kind, limit)).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("timed out after {}ms", probe.timeout_ms)),
+    };
+
+    ProbeResult {
+        probe_type: probe.kind.type_name().to_string(),
+        success: outcome.is_ok(),
+        duration_seconds: started.elapsed().as_secs_f64(),
+        checked_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        error: outcome.err(),
+    }
+}
+
+async fn execute(kind: &ProbeKind, limit: Duration) -> Result<(), String> {
+    match kind {
+        ProbeKind::Tcp { address } => {
+            TcpStream::connect(address)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("connect {} failed: {}", address, e))
+        }
+        ProbeKind::Http { url, expected_status } => {
+            let client = reqwest::Client::builder()
+                .timeout(limit)
+                .build()
+                .map_err(|e| e.to_string())?;
+            let response = client.get(url)
+                .send()
+                .await
+                .map_err(|e| format!("GET {} failed: {}", url, e))?;
+
+            let status = response.status().as_u16();
+            if status == *expected_status {
+                Ok(())
+            } else {
+                Err(format!("GET {} returned {}, expected {}", url, status, expected_status))
+            }
+        }
+        ProbeKind::Exec { command, args } => {
+            let status = Command::new(command)
+                .args(args)
+                .kill_on_drop(true)
+                .status()
+                .await
+                .map_err(|e| format!("exec {} failed: {}", command, e))?;
+
+            if status.success() {
+                Ok(())
+            } else {
+                Err(format!("{} exited with {}", command, status))
+            }
+        }
+    }
+}
+
+/// 对所有注册进程执行一轮探测，并写回状态
+pub async fn probe_all(data: &AppState) {
+    let probes: Vec<(String, ProbeConfig)> = {
+        let state = data.lock().unwrap();
+        state.processes.values()
+            .flat_map(|status| {
+                status.config.probes.iter()
+                    .map(|probe| (status.config.name.clone(), probe.clone()))
+            })
+            .collect()
+    };
+
+    if probes.is_empty() {
+        return;
+    }
+
+    let mut tasks = JoinSet::new();
+    for (name, probe) in probes {
+        tasks.spawn(async move {
+            let result = run_probe(&probe).await;
+            (name, probe.label().to_string(), result)
+        });
+    }
+
+    let mut results: HashMap<String, HashMap<String, ProbeResult>> = HashMap::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((name, label, result)) => {
+                if !result.success {
+                    log::debug!("Probe '{}' of '{}' failed: {:?}", label, name, result.error);
+                }
+                results.entry(name).or_default().insert(label, result);
+            }
+            Err(e) => log::warn!("Probe task failed: {}", e),
+        }
+    }
+
+    let mut state = data.lock().unwrap();
+    for (name, probe_results) in results {
+        // 探测期间进程可能已被注销
+        if let Some(status) = state.processes.get_mut(&name) {
+            status.probe_results = probe_results;
+        }
+    }
+}
+
+/// 启动后台探测任务
+pub fn spawn_prober(data: AppState, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            probe_all(&data).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(kind: ProbeKind) -> ProbeConfig {
+        ProbeConfig { name: String::new(), kind, timeout_ms: 1000 }
+    }
+
+    #[test]
+    fn test_validate_probes() {
+        let tcp = probe(ProbeKind::Tcp { address: "127.0.0.1:80".to_string() });
+        let exec = probe(ProbeKind::Exec { command: "true".to_string(), args: Vec::new() });
+
+        assert!(validate_probes(std::slice::from_ref(&tcp), false).is_ok());
+        assert!(validate_probes(std::slice::from_ref(&exec), false).unwrap_err().contains("--allow-exec-probes"));
+        assert!(validate_probes(&[exec], true).is_ok());
+
+        // 未命名的探测以类型为名称，同类型需显式命名
+        assert!(validate_probes(&[tcp.clone(), tcp.clone()], false).unwrap_err().contains("duplicate probe name 'tcp'"));
+        let named = ProbeConfig { name: "admin".to_string(), ..tcp.clone() };
+        assert!(validate_probes(&[tcp, named], false).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_probe() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let result = run_probe(&probe(ProbeKind::Tcp { address })).await;
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.probe_type, "tcp");
+
+        drop(listener);
+    }
+
+    #[tokio::test]
+    async fn test_exec_probe() {
+        let ok = run_probe(&probe(ProbeKind::Exec { command: "true".into(), args: vec![] })).await;
+        assert!(ok.success);
+
+        let failed = run_probe(&probe(ProbeKind::Exec { command: "false".into(), args: vec![] })).await;
+        assert!(!failed.success);
+        assert!(failed.error.is_some());
+    }
+}
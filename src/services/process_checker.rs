@@ -1,4 +1,4 @@
-use sysinfo::{System, ProcessesToUpdate, Pid};
+use sysinfo::{System, ProcessesToUpdate};
 use regex::Regex;
 
 /// 检查进程是否正在运行
@@ -187,7 +187,7 @@ mod tests {
 
     #[test]
     fn test_find_current_process() {
-        let current_pid = process::id() as i32;
+        let _current_pid = process::id() as i32;
         let found = check_process_running("cargo");
         println!("Found cargo process: {}", found);
     }
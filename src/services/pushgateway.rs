@@ -0,0 +1,160 @@
+//! Pushgateway 推送
+//!
+//! 按推送间隔将 `MetricsRegistry` 中的全部指标族以文本格式 PUT 到分组
+//! `/metrics/job/<job>/<label>/<value>...`，退出时再推送一次或删除该分组，
+//! 适用于 CI runner 等短生命周期主机。
+
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
+use crate::metrics::{ScrapeFilter, METRICS};
+
+/// Pushgateway 配置
+#[derive(Debug, Clone)]
+pub struct PushgatewayConfig {
+    /// Pushgateway 地址，例如 http://pushgateway:9091
+    pub url: String,
+    /// 分组键中的 job
+    pub job: String,
+    /// job 之外的分组标签，按顺序拼接到 URL
+    pub grouping: Vec<(String, String)>,
+    /// 单次请求超时
+    pub timeout: Duration,
+    /// 退出时删除分组而不是做最后一次推送
+    pub delete_on_shutdown: bool,
+}
+
+/// Pushgateway 客户端
+pub struct Pushgateway {
+    client: reqwest::Client,
+    config: PushgatewayConfig,
+    group_url: String,
+}
+
+impl Pushgateway {
+    pub fn new(config: PushgatewayConfig) -> anyhow::Result<Self> {
+        if config.job.is_empty() {
+            bail!("Pushgateway job must not be empty");
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .context("Failed to build HTTP client")?;
+        let group_url = group_url(&config.url, &config.job, &config.grouping);
+
+        Ok(Self { client, config, group_url })
+    }
+
+    /// 分组 URL
+    pub fn group_url(&self) -> &str {
+        &self.group_url
+    }
+
+    /// 以 PUT 替换整个分组，进程指标取自后台采集任务刷新后的状态
+    pub async fn push(&self) -> anyhow::Result<()> {
+        let body = METRICS.render(&ScrapeFilter::default())
+            .map_err(|e| anyhow!("Failed to render metrics: {}", e))?;
+
+        let response = self.client
+            .put(&self.group_url)
+            .header(reqwest::header::CONTENT_TYPE, "text/plain; version=0.0.4")
+            .body(body)
+            .send()
+            .await
+            .with_context(|| format!("PUT {} failed", self.group_url))?;
+
+        check_response(response).await
+    }
+
+    /// 删除整个分组
+    pub async fn delete(&self) -> anyhow::Result<()> {
+        let response = self.client
+            .delete(&self.group_url)
+            .send()
+            .await
+            .with_context(|| format!("DELETE {} failed", self.group_url))?;
+
+        check_response(response).await
+    }
+
+    /// 退出时的收尾：删除分组或推送最后一次
+    pub async fn shutdown(&self) {
+        if self.config.delete_on_shutdown {
+            match self.delete().await {
+                Ok(()) => log::info!("🗑️  Deleted Pushgateway group {}", self.group_url),
+                Err(e) => log::warn!("Failed to delete Pushgateway group: {:#}", e),
+            }
+        } else {
+            match self.push().await {
+                Ok(()) => log::info!("📤 Pushed final metrics to {}", self.group_url),
+                Err(e) => log::warn!("Final Pushgateway push failed: {:#}", e),
+            }
+        }
+    }
+}
+
+async fn check_response(response: reqwest::Response) -> anyhow::Result<()> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.unwrap_or_default();
+    bail!("Pushgateway returned {} {}", status, body.trim())
+}
+
+/// 拼接分组 URL
+///
+/// 值包含 URL 保留字符（如 `/`）或为空时使用 Pushgateway 的 `@base64` 编码
+fn group_url(base: &str, job: &str, grouping: &[(String, String)]) -> String {
+    let mut url = format!("{}/metrics", base.trim_end_matches('/'));
+    for (name, value) in std::iter::once(("job", job)).chain(grouping.iter().map(|(k, v)| (k.as_str(), v.as_str()))) {
+        let plain = !value.is_empty()
+            && value.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~'));
+        if plain {
+            url.push_str(&format!("/{}/{}", name, value));
+        } else if value.is_empty() {
+            // 空值需写成单个 "="
+            url.push_str(&format!("/{}@base64/=", name));
+        } else {
+            url.push_str(&format!("/{}@base64/{}", name, URL_SAFE_NO_PAD.encode(value)));
+        }
+    }
+    url
+}
+
+/// 启动后台 Pushgateway 推送任务
+pub fn spawn_pushgateway(pushgateway: std::sync::Arc<Pushgateway>, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match pushgateway.push().await {
+                Ok(()) => log::debug!("✓ Pushed metrics to {}", pushgateway.group_url()),
+                Err(e) => log::warn!("Pushgateway push failed: {:#}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_url() {
+        let grouping = vec![
+            ("hostname".to_string(), "runner-01".to_string()),
+            ("path".to_string(), "/var/tmp".to_string()),
+            ("empty".to_string(), String::new()),
+        ];
+
+        assert_eq!(
+            group_url("http://pushgateway:9091/", "process-exporter", &grouping),
+            "http://pushgateway:9091/metrics/job/process-exporter/hostname/runner-01/path@base64/L3Zhci90bXA/empty@base64/=",
+        );
+    }
+}
@@ -0,0 +1,437 @@
+//! Prometheus remote_write 推送
+//!
+//! 按推送间隔采集一次指标，编码为 snappy 压缩的 protobuf `WriteRequest` 后 POST 到接收端。
+//! 可重试的失败（网络错误、5xx、429）按指数退避重试，仍失败时写入磁盘缓冲目录，
+//! 下次推送前按时间顺序补发。
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context};
+use prometheus::proto::{LabelPair, MetricFamily, MetricType};
+use prost::Message;
+use reqwest::StatusCode;
+
+use crate::metrics::METRICS;
+
+/// 首次重试前的等待时间
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// 重试等待时间上限
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// 缓冲文件扩展名
+const BUFFER_EXTENSION: &str = "snappy";
+
+/// remote_write 1.0 协议消息（prometheus/prompb/remote.proto 与 types.proto 的子集）
+#[derive(Clone, PartialEq, Message)]
+pub struct WriteRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub timeseries: Vec<TimeSeries>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TimeSeries {
+    /// 按名称排序的标签，包含 `__name__`
+    #[prost(message, repeated, tag = "1")]
+    pub labels: Vec<Label>,
+    #[prost(message, repeated, tag = "2")]
+    pub samples: Vec<Sample>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Label {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub value: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Sample {
+    #[prost(double, tag = "1")]
+    pub value: f64,
+    /// 毫秒时间戳
+    #[prost(int64, tag = "2")]
+    pub timestamp: i64,
+}
+
+/// remote_write 配置
+#[derive(Debug, Clone)]
+pub struct RemoteWriteConfig {
+    /// 接收端地址，例如 http://prometheus:9090/api/v1/write
+    pub url: String,
+    /// 附加到所有时间序列上的外部标签（序列自身已有同名标签时不覆盖）
+    pub external_labels: Vec<(String, String)>,
+    /// 单次请求超时
+    pub timeout: Duration,
+    /// 每次发送的最大重试次数
+    pub max_retries: u32,
+    /// 磁盘缓冲目录，未配置时发送失败的数据直接丢弃
+    pub buffer_dir: Option<PathBuf>,
+    /// 缓冲目录最多保留的请求数，超出时丢弃最旧的
+    pub buffer_max_files: usize,
+    /// Bearer Token 认证
+    pub bearer_token: Option<String>,
+    /// Basic 认证（用户名，密码）
+    pub basic_auth: Option<(String, Option<String>)>,
+}
+
+/// 单次发送失败的原因
+enum SendError {
+    /// 网络错误、5xx、429，稍后可重试
+    Retryable(String),
+    /// 其他 4xx，重试也不会成功
+    Permanent(String),
+}
+
+/// remote_write 客户端
+pub struct RemoteWriter {
+    client: reqwest::Client,
+    config: RemoteWriteConfig,
+    seq: AtomicU64,
+}
+
+impl RemoteWriter {
+    pub fn new(config: RemoteWriteConfig) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        if let Some(dir) = &config.buffer_dir {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create buffer directory {}", dir.display()))?;
+        }
+
+        Ok(Self { client, config, seq: AtomicU64::new(0) })
+    }
+
+    /// 推送一批指标；失败时写入磁盘缓冲（若已配置）并返回错误
+    pub async fn push(&self, families: &[MetricFamily]) -> anyhow::Result<()> {
+        let request = to_write_request(families, &self.config.external_labels, now_millis());
+        if request.timeseries.is_empty() {
+            return Ok(());
+        }
+        let payload = encode(&request)?;
+
+        // 先补发积压的数据，保证接收端按时间顺序写入
+        if !self.flush_buffer().await {
+            self.buffer(&payload).await?;
+            bail!("receiver unavailable, buffered {} series", request.timeseries.len());
+        }
+
+        match self.send(&payload).await {
+            Ok(()) => Ok(()),
+            Err(SendError::Permanent(e)) => bail!("remote write rejected, dropped: {}", e),
+            Err(SendError::Retryable(e)) => {
+                self.buffer(&payload).await?;
+                bail!("remote write failed, buffered: {}", e)
+            }
+        }
+    }
+
+    /// 带指数退避的发送
+    async fn send(&self, payload: &[u8]) -> Result<(), SendError> {
+        let mut backoff = INITIAL_BACKOFF;
+        let mut attempt = 0;
+        loop {
+            match self.send_once(payload).await {
+                Err(SendError::Retryable(e)) if attempt < self.config.max_retries => {
+                    attempt += 1;
+                    log::debug!("Remote write attempt {} failed: {}, retrying in {:?}", attempt, e, backoff);
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn send_once(&self, payload: &[u8]) -> Result<(), SendError> {
+        let mut request = self.client
+            .post(&self.config.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/x-protobuf")
+            .header(reqwest::header::CONTENT_ENCODING, "snappy")
+            .header(reqwest::header::USER_AGENT, concat!("process-exporter/", env!("CARGO_PKG_VERSION")))
+            .header("X-Prometheus-Remote-Write-Version", "0.1.0")
+            .body(payload.to_vec());
+
+        if let Some(token) = &self.config.bearer_token {
+            request = request.bearer_auth(token);
+        } else if let Some((username, password)) = &self.config.basic_auth {
+            request = request.basic_auth(username, password.as_ref());
+        }
+
+        let response = request.send()
+            .await
+            .map_err(|e| SendError::Retryable(e.to_string()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        let message = format!("{} {}", status, body.trim());
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            Err(SendError::Retryable(message))
+        } else {
+            Err(SendError::Permanent(message))
+        }
+    }
+
+    /// 按时间顺序补发缓冲目录中的请求，全部发送成功（或无积压）时返回 true
+    async fn flush_buffer(&self) -> bool {
+        for path in self.buffered_files().await {
+            let payload = match tokio::fs::read(&path).await {
+                Ok(payload) => payload,
+                Err(e) => {
+                    log::warn!("Failed to read buffered remote write {}: {}", path.display(), e);
+                    let _ = tokio::fs::remove_file(&path).await;
+                    continue;
+                }
+            };
+
+            match self.send(&payload).await {
+                Ok(()) => log::debug!("✓ Flushed buffered remote write {}", path.display()),
+                Err(SendError::Permanent(e)) => {
+                    log::warn!("Buffered remote write {} rejected, dropped: {}", path.display(), e);
+                }
+                Err(SendError::Retryable(_)) => return false,
+            }
+            let _ = tokio::fs::remove_file(&path).await;
+        }
+        true
+    }
+
+    /// 将请求写入磁盘缓冲，超出上限时丢弃最旧的
+    async fn buffer(&self, payload: &[u8]) -> anyhow::Result<()> {
+        let Some(dir) = &self.config.buffer_dir else {
+            return Ok(());
+        };
+
+        // 文件名按时间戳 + 序号排序，即为发送顺序
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        let path = dir.join(format!("{:020}-{:06}.{}", now_millis(), seq % 1_000_000, BUFFER_EXTENSION));
+        tokio::fs::write(&path, payload)
+            .await
+            .with_context(|| format!("Failed to write buffer file {}", path.display()))?;
+
+        let files = self.buffered_files().await;
+        if files.len() > self.config.buffer_max_files {
+            let excess = files.len() - self.config.buffer_max_files;
+            log::warn!("Remote write buffer full, dropping {} oldest request(s)", excess);
+            for old in &files[..excess] {
+                let _ = tokio::fs::remove_file(old).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn buffered_files(&self) -> Vec<PathBuf> {
+        let Some(dir) = &self.config.buffer_dir else {
+            return Vec::new();
+        };
+        let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+            return Vec::new();
+        };
+
+        let mut files = Vec::new();
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == BUFFER_EXTENSION) {
+                files.push(path);
+            }
+        }
+        files.sort();
+        files
+    }
+}
+
+/// 将指标族转换为 remote_write 请求
+///
+/// histogram 与 summary 按文本格式的约定展开为 `_bucket` / `_sum` / `_count` 等序列
+pub fn to_write_request(
+    families: &[MetricFamily],
+    external_labels: &[(String, String)],
+    timestamp: i64,
+) -> WriteRequest {
+    let mut timeseries = Vec::new();
+
+    for family in families {
+        let name = family.get_name();
+        for metric in family.get_metric() {
+            let labels = metric.get_label();
+            let mut push = |suffix: &str, extra: Option<(&str, String)>, value: f64| {
+                timeseries.push(TimeSeries {
+                    labels: series_labels(&format!("{}{}", name, suffix), labels, extra, external_labels),
+                    samples: vec![Sample { value, timestamp }],
+                });
+            };
+
+            match family.get_field_type() {
+                MetricType::COUNTER => push("", None, metric.get_counter().get_value()),
+                MetricType::GAUGE => push("", None, metric.get_gauge().get_value()),
+                MetricType::UNTYPED => push("", None, metric.get_untyped().get_value()),
+                MetricType::HISTOGRAM => {
+                    let histogram = metric.get_histogram();
+                    for bucket in histogram.get_bucket() {
+                        push("_bucket", Some(("le", bucket.get_upper_bound().to_string())), bucket.get_cumulative_count() as f64);
+                    }
+                    if histogram.get_bucket().last().map(|b| b.get_upper_bound()) != Some(f64::INFINITY) {
+                        push("_bucket", Some(("le", "+Inf".to_string())), histogram.get_sample_count() as f64);
+                    }
+                    push("_sum", None, histogram.get_sample_sum());
+                    push("_count", None, histogram.get_sample_count() as f64);
+                }
+                MetricType::SUMMARY => {
+                    let summary = metric.get_summary();
+                    for quantile in summary.get_quantile() {
+                        push("", Some(("quantile", quantile.get_quantile().to_string())), quantile.get_value());
+                    }
+                    push("_sum", None, summary.get_sample_sum());
+                    push("_count", None, summary.get_sample_count() as f64);
+                }
+            }
+        }
+    }
+
+    WriteRequest { timeseries }
+}
+
+/// 组装单个序列的标签：`__name__` + 样本标签 + 附加标签 + 外部标签，按名称排序
+fn series_labels(
+    name: &str,
+    labels: &[LabelPair],
+    extra: Option<(&str, String)>,
+    external_labels: &[(String, String)],
+) -> Vec<Label> {
+    let mut result: Vec<Label> = std::iter::once(Label { name: "__name__".to_string(), value: name.to_string() })
+        .chain(labels.iter().map(|l| Label { name: l.get_name().to_string(), value: l.get_value().to_string() }))
+        .chain(extra.map(|(k, v)| Label { name: k.to_string(), value: v }))
+        .collect();
+
+    for (key, value) in external_labels {
+        if !result.iter().any(|l| &l.name == key) {
+            result.push(Label { name: key.clone(), value: value.clone() });
+        }
+    }
+
+    result.sort_by(|a, b| a.name.cmp(&b.name));
+    result
+}
+
+/// protobuf 编码后做 snappy 块压缩（remote_write 不使用 framed 格式）
+fn encode(request: &WriteRequest) -> anyhow::Result<Vec<u8>> {
+    snap::raw::Encoder::new()
+        .compress_vec(&request.encode_to_vec())
+        .context("Failed to snappy-compress write request")
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+/// 启动后台 remote_write 推送任务
+pub fn spawn_remote_writer(writer: RemoteWriter, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+
+            // 进程指标在 gather 时读取后台采集任务刷新后的状态
+            let families = METRICS.gather();
+
+            match writer.push(&families).await {
+                Ok(()) => log::debug!("✓ Remote write pushed {} metric families", families.len()),
+                Err(e) => log::warn!("Remote write failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+    use prometheus::{CounterVec, Opts, Registry};
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    /// 本地替身接收端：按预设顺序返回状态码，记录成功解码的请求
+    #[derive(Default)]
+    struct Receiver {
+        statuses: Mutex<VecDeque<u16>>,
+        received: Mutex<Vec<WriteRequest>>,
+    }
+
+    async fn receive(req: HttpRequest, body: web::Bytes, receiver: web::Data<Receiver>) -> HttpResponse {
+        assert_eq!(req.headers().get("content-encoding").unwrap(), "snappy");
+        assert_eq!(req.headers().get("x-prometheus-remote-write-version").unwrap(), "0.1.0");
+
+        let status = receiver.statuses.lock().unwrap().pop_front().unwrap_or(204);
+        if status == 204 {
+            let raw = snap::raw::Decoder::new().decompress_vec(&body).unwrap();
+            receiver.received.lock().unwrap().push(WriteRequest::decode(raw.as_slice()).unwrap());
+        }
+        HttpResponse::build(actix_web::http::StatusCode::from_u16(status).unwrap()).finish()
+    }
+
+    #[actix_web::test]
+    async fn test_remote_write_buffers_until_receiver_recovers() {
+        let receiver = web::Data::new(Receiver::default());
+        receiver.statuses.lock().unwrap().push_back(503);
+
+        let app_receiver = receiver.clone();
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(app_receiver.clone())
+                .route("/api/v1/write", web::post().to(receive))
+        })
+            .workers(1)
+            .bind("127.0.0.1:0")
+            .unwrap();
+        let addr = server.addrs()[0];
+        actix_web::rt::spawn(server.run());
+
+        let buffer_dir = std::env::temp_dir().join(format!("process-exporter-rw-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&buffer_dir);
+        let writer = RemoteWriter::new(RemoteWriteConfig {
+            url: format!("http://{}/api/v1/write", addr),
+            external_labels: vec![("instance".into(), "host-a".into())],
+            timeout: Duration::from_secs(5),
+            max_retries: 0,
+            buffer_dir: Some(buffer_dir.clone()),
+            buffer_max_files: 10,
+            bearer_token: None,
+            basic_auth: None,
+        }).unwrap();
+
+        let registry = Registry::new();
+        let counter = CounterVec::new(Opts::new("process_disk_read_bytes", "Total disk read bytes"), &["name"]).unwrap();
+        counter.with_label_values(&["nginx"]).inc_by(1024.0);
+        registry.register(Box::new(counter)).unwrap();
+
+        // 接收端返回 503：写入磁盘缓冲
+        assert!(writer.push(&registry.gather()).await.is_err());
+        assert_eq!(writer.buffered_files().await.len(), 1);
+
+        // 接收端恢复：先补发缓冲，再发送本次数据
+        writer.push(&registry.gather()).await.unwrap();
+        assert!(writer.buffered_files().await.is_empty());
+
+        let received = receiver.received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert!(received[0].timeseries[0].samples[0].timestamp <= received[1].timeseries[0].samples[0].timestamp);
+
+        let series = &received[1].timeseries[0];
+        let labels: Vec<(&str, &str)> = series.labels.iter().map(|l| (l.name.as_str(), l.value.as_str())).collect();
+        assert_eq!(labels, vec![("__name__", "process_disk_read_bytes"), ("instance", "host-a"), ("name", "nginx")]);
+        assert_eq!(series.samples[0].value, 1024.0);
+
+        let _ = std::fs::remove_dir_all(&buffer_dir);
+    }
+}
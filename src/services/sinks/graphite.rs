@@ -0,0 +1,91 @@
+//! Graphite plaintext sink
+//!
+//! 使用 Graphite 1.1 的 tagged series 格式，每个字段一行：
+//! `process.cpu_usage_percent;name=nginx;hostname=web-1;team=web 1.5 <秒>`
+
+use std::fmt::Write;
+
+use async_trait::async_trait;
+
+use super::{process_fields, process_tags, Sink, Snapshot, Transport};
+
+/// Graphite plaintext sink
+pub struct GraphiteSink {
+    client: reqwest::Client,
+    transport: Transport,
+    prefix: String,
+}
+
+impl GraphiteSink {
+    pub fn new(transport: Transport, prefix: String) -> Self {
+        Self { client: reqwest::Client::new(), transport, prefix }
+    }
+}
+
+#[async_trait]
+impl Sink for GraphiteSink {
+    fn describe(&self) -> String {
+        format!("graphite+{}", self.transport)
+    }
+
+    async fn write(&self, snapshot: &Snapshot) -> anyhow::Result<()> {
+        self.transport.send(&self.client, &encode(snapshot, &self.prefix)).await
+    }
+}
+
+/// 编码为 plaintext 协议（时间戳精度为秒）
+pub fn encode(snapshot: &Snapshot, prefix: &str) -> String {
+    let mut out = String::new();
+    let timestamp = snapshot.timestamp_secs();
+
+    for status in &snapshot.processes {
+        let tags: String = process_tags(status, &snapshot.hostname)
+            .into_iter()
+            // Graphite 不接受空的 tag 值
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(key, value)| format!(";{}={}", sanitize(key), sanitize(value)))
+            .collect();
+
+        for (field, value, _) in process_fields(status) {
+            let path = if prefix.is_empty() {
+                field.to_string()
+            } else {
+                format!("{}.{}", prefix, field)
+            };
+            let _ = writeln!(out, "{}{} {} {}", path, tags, value, timestamp);
+        }
+    }
+    out
+}
+
+/// tag 中不允许出现 `;`、`!`、`^`、`=`、`~` 与空白
+fn sanitize(text: &str) -> String {
+    text.chars()
+        .map(|c| if matches!(c, ';' | '!' | '^' | '=' | '~') || c.is_whitespace() { '_' } else { c })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProcessConfig, ProcessStats, ProcessStatus};
+
+    #[test]
+    fn test_encode_plaintext() {
+        let mut config = ProcessConfig::new("nginx", "nginx");
+        config.labels.insert("team".to_string(), "web;ops".to_string());
+        let mut status = ProcessStatus::new(config);
+        status.pid = Some(1);
+        status.stats = ProcessStats { cpu_usage: 1.5, memory_bytes: 4096, ..ProcessStats::default() };
+        let snapshot = Snapshot {
+            hostname: "web-1".into(),
+            timestamp_nanos: 1_700_000_000_500_000_000,
+            processes: vec![status],
+        };
+
+        let text = encode(&snapshot, "process");
+        assert!(text.contains("process.up;name=nginx;hostname=web-1;team=web_ops 1 1700000000\n"));
+        assert!(text.contains("process.cpu_usage_percent;name=nginx;hostname=web-1;team=web_ops 1.5 1700000000\n"));
+        assert!(text.contains("process.memory_bytes;name=nginx;hostname=web-1;team=web_ops 4096 1700000000\n"));
+    }
+}
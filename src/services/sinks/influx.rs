@@ -0,0 +1,111 @@
+//! InfluxDB line protocol sink
+//!
+//! 每个进程一行：`process,name=nginx,hostname=web-1,team=web up=1i,cpu_usage_percent=1.5,... <ns>`
+
+use std::fmt::Write;
+
+use async_trait::async_trait;
+
+use super::{process_fields, process_tags, Sink, Snapshot, Transport};
+
+/// measurement 名称
+const MEASUREMENT: &str = "process";
+
+/// InfluxDB line protocol sink
+pub struct InfluxSink {
+    client: reqwest::Client,
+    transport: Transport,
+}
+
+impl InfluxSink {
+    pub fn new(transport: Transport) -> Self {
+        Self { client: reqwest::Client::new(), transport }
+    }
+}
+
+#[async_trait]
+impl Sink for InfluxSink {
+    fn describe(&self) -> String {
+        format!("influx+{}", self.transport)
+    }
+
+    async fn write(&self, snapshot: &Snapshot) -> anyhow::Result<()> {
+        self.transport.send(&self.client, &encode(snapshot)).await
+    }
+}
+
+/// 编码为 line protocol（时间戳精度为纳秒）
+pub fn encode(snapshot: &Snapshot) -> String {
+    let mut out = String::new();
+
+    for status in &snapshot.processes {
+        out.push_str(MEASUREMENT);
+        for (key, value) in process_tags(status, &snapshot.hostname) {
+            // 空值的 tag 不合法，直接跳过
+            if !value.is_empty() {
+                let _ = write!(out, ",{}={}", escape(key), escape(value));
+            }
+        }
+
+        let fields: Vec<String> = process_fields(status)
+            .into_iter()
+            .map(|(key, value, integer)| {
+                if integer {
+                    format!("{}={}i", key, value as i64)
+                } else {
+                    format!("{}={}", key, value)
+                }
+            })
+            .collect();
+        let _ = writeln!(out, " {} {}", fields.join(","), snapshot.timestamp_nanos);
+    }
+    out
+}
+
+/// tag 键与值中的逗号、等号、空格需要转义
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            ',' | '=' | ' ' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push(' '),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProcessConfig, ProcessStats, ProcessStatus};
+
+    fn status(name: &str, is_running: bool) -> ProcessStatus {
+        let mut config = ProcessConfig::new(name, name);
+        config.labels.insert("env".to_string(), "prod west".to_string());
+        let mut status = ProcessStatus::new(config);
+        status.is_running = is_running;
+        status.stats = ProcessStats { cpu_usage: 2.5, memory_bytes: 4096, ..ProcessStats::default() };
+        status
+    }
+
+    #[test]
+    fn test_encode_line_protocol() {
+        let snapshot = Snapshot {
+            hostname: "web-1".into(),
+            timestamp_nanos: 1_700_000_000_000_000_000,
+            processes: vec![status("nginx", true), status("redis", false)],
+        };
+
+        let text = encode(&snapshot);
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with(
+            "process,name=nginx,hostname=web-1,env=prod\\ west up=1i,cpu_usage_percent=2.5,cpu_time_seconds=0,memory_bytes=4096i,"
+        ));
+        assert!(lines[0].ends_with(" 1700000000000000000"));
+        assert_eq!(lines[1], "process,name=redis,hostname=web-1,env=prod\\ west up=0i 1700000000000000000");
+    }
+}
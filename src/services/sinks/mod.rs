@@ -0,0 +1,171 @@
+//! 输出 sink
+//!
+//! 每个推送周期刷新一次进程状态，生成 [`Snapshot`] 后交给所有已配置的 sink 写出。
+//! 新的输出格式只需实现 [`Sink`]，传输方式可复用 [`Transport`]。
+
+pub mod graphite;
+pub mod influx;
+pub mod otlp;
+pub mod transport;
+
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use sysinfo::System;
+
+use crate::models::ProcessStatus;
+use crate::state::AppState;
+
+pub use graphite::GraphiteSink;
+pub use influx::InfluxSink;
+pub use otlp::{OtlpConfig, OtlpExporter};
+pub use transport::Transport;
+
+/// 一次采集的进程状态快照
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// 主机名
+    pub hostname: String,
+    /// 采集时间（Unix 纳秒）
+    pub timestamp_nanos: u128,
+    /// 所有注册进程
+    pub processes: Vec<ProcessStatus>,
+}
+
+impl Snapshot {
+    /// 采集时间（Unix 秒）
+    pub fn timestamp_secs(&self) -> u64 {
+        (self.timestamp_nanos / 1_000_000_000) as u64
+    }
+}
+
+/// 输出 sink
+#[async_trait]
+pub trait Sink: Send + Sync {
+    /// 用于日志的描述
+    fn describe(&self) -> String;
+
+    /// 写出一次快照
+    async fn write(&self, snapshot: &Snapshot) -> anyhow::Result<()>;
+}
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinkFormat {
+    Influx,
+    Graphite,
+}
+
+impl std::fmt::Display for SinkFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SinkFormat::Influx => write!(f, "influx"),
+            SinkFormat::Graphite => write!(f, "graphite"),
+        }
+    }
+}
+
+/// `--sink` 参数：`<format>+<transport>://<address>`
+///
+/// 例如 `influx+udp://127.0.0.1:8089`、`influx+http://influxdb:8086/write?db=procs`、
+/// `graphite+tcp://graphite:2003`
+#[derive(Debug, Clone)]
+pub struct SinkSpec {
+    pub format: SinkFormat,
+    pub transport: Transport,
+}
+
+impl FromStr for SinkSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (format, address) = s.split_once('+')
+            .ok_or_else(|| format!("expected <format>+<transport>://<address>, got '{}'", s))?;
+        let format = match format.to_ascii_lowercase().as_str() {
+            "influx" | "influxdb" => SinkFormat::Influx,
+            "graphite" => SinkFormat::Graphite,
+            other => return Err(format!("unknown sink format '{}', expected influx or graphite", other)),
+        };
+        Ok(Self { format, transport: address.parse()? })
+    }
+}
+
+/// 读取后台采集任务最近一次刷新后的状态快照
+pub async fn snapshot(data: &AppState) -> Snapshot {
+    let hostname = System::host_name().unwrap_or_else(|| "unknown".to_string());
+    let processes = {
+        let state = data.lock().unwrap();
+        state.processes.values().cloned().collect()
+    };
+
+    Snapshot {
+        hostname,
+        timestamp_nanos: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos(),
+        processes,
+    }
+}
+
+/// 启动后台 sink 写出任务
+pub fn spawn_sinks(data: AppState, sinks: Vec<Box<dyn Sink>>, interval: Duration) {
+    if sinks.is_empty() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let snapshot = snapshot(&data).await;
+
+            for sink in &sinks {
+                match sink.write(&snapshot).await {
+                    Ok(()) => log::debug!("✓ Wrote {} processes to {}", snapshot.processes.len(), sink.describe()),
+                    Err(e) => log::warn!("Sink {} failed: {:#}", sink.describe(), e),
+                }
+            }
+        }
+    });
+}
+
+/// 进程的数值字段（字段名, 值, 是否整数），供行格式 sink 共用
+///
+/// 进程未运行时只输出 `up`
+pub(crate) fn process_fields(status: &ProcessStatus) -> Vec<(&'static str, f64, bool)> {
+    let mut fields = vec![("up", if status.is_running { 1.0 } else { 0.0 }, true)];
+    if !(status.is_running && status.stats.is_valid()) {
+        return fields;
+    }
+
+    let stats = &status.stats;
+    fields.extend([
+        ("cpu_usage_percent", stats.cpu_usage as f64, false),
+        ("cpu_time_seconds", stats.cpu_time_seconds, false),
+        ("memory_bytes", stats.memory_bytes as f64, true),
+        ("memory_percent", stats.memory_percent as f64, false),
+        ("virtual_memory_bytes", stats.virtual_memory_bytes as f64, true),
+        ("disk_read_bytes", stats.disk_read_bytes as f64, true),
+        ("disk_written_bytes", stats.disk_written_bytes as f64, true),
+        ("network_tx_bytes", stats.network_tx_bytes as f64, true),
+        ("network_rx_bytes", stats.network_rx_bytes as f64, true),
+        ("network_tx_packets", stats.network_tx_packets as f64, true),
+        ("network_rx_packets", stats.network_rx_packets as f64, true),
+    ]);
+    fields
+}
+
+/// 进程的标签：name、hostname 与按键排序的注册标签（同名时内置标签优先）
+pub(crate) fn process_tags<'a>(status: &'a ProcessStatus, hostname: &'a str) -> Vec<(&'a str, &'a str)> {
+    let mut tags = vec![("name", status.config.name.as_str()), ("hostname", hostname)];
+
+    let mut labels: Vec<(&str, &str)> = status.config.labels.iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .filter(|(k, _)| *k != "name" && *k != "hostname")
+        .collect();
+    labels.sort();
+    tags.extend(labels);
+    tags
+}
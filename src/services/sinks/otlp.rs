@@ -0,0 +1,242 @@
+//! OpenTelemetry OTLP/HTTP 指标导出（JSON 编码）
+//!
+//! 每个注册进程对应一个 Resource，资源属性取自主机名、进程信息与注册标签；
+//! `ProcessStats` 按 OTel process 语义约定映射：
+//! - `process.cpu.time`：累计 CPU 时间（s，Sum 单调）
+//! - `process.memory.usage` / `process.memory.virtual`：内存（By，Sum 非单调）
+//! - `process.disk.io`：磁盘读写（By，Sum 单调，属性 `disk.io.direction`）
+//! - `process.network.io`：网络收发（By，Sum 单调，属性 `network.io.direction`）
+
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{Sink, Snapshot};
+use crate::models::ProcessStatus;
+
+/// AggregationTemporality.CUMULATIVE
+const CUMULATIVE: u8 = 2;
+
+/// OTLP 导出配置
+#[derive(Debug, Clone)]
+pub struct OtlpConfig {
+    /// Collector 地址，例如 http://otel-collector:4318（自动补全 /v1/metrics）
+    pub endpoint: String,
+    /// 附加的请求头（认证等）
+    pub headers: Vec<(String, String)>,
+    /// 单次请求超时
+    pub timeout: Duration,
+}
+
+/// OTLP/HTTP 导出器
+pub struct OtlpExporter {
+    client: reqwest::Client,
+    url: String,
+    headers: Vec<(String, String)>,
+}
+
+impl OtlpExporter {
+    pub fn new(config: OtlpConfig) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        let endpoint = config.endpoint.trim_end_matches('/');
+        let url = if endpoint.ends_with("/v1/metrics") {
+            endpoint.to_string()
+        } else {
+            format!("{}/v1/metrics", endpoint)
+        };
+
+        Ok(Self { client, url, headers: config.headers })
+    }
+}
+
+#[async_trait]
+impl Sink for OtlpExporter {
+    fn describe(&self) -> String {
+        format!("otlp+{}", self.url)
+    }
+
+    async fn write(&self, snapshot: &Snapshot) -> anyhow::Result<()> {
+        let body = build_request(&snapshot.processes, &snapshot.hostname, snapshot.timestamp_nanos);
+
+        let mut request = self.client.post(&self.url).json(&body);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+
+        let response = request.send()
+            .await
+            .with_context(|| format!("POST {} failed", self.url))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!("OTLP collector returned {} {}", status, body.trim());
+        }
+        Ok(())
+    }
+}
+
+/// 构造 ExportMetricsServiceRequest（OTLP JSON 编码，64 位整数以字符串表示）
+pub fn build_request<'a, I>(processes: I, hostname: &str, now_nanos: u128) -> Value
+where
+    I: IntoIterator<Item = &'a ProcessStatus>,
+{
+    let resource_metrics: Vec<Value> = processes
+        .into_iter()
+        .filter(|status| status.is_running && status.stats.is_valid())
+        .map(|status| {
+            let stats = &status.stats;
+            // eBPF 计数从 PID 加入白名单开始，其余累计值从进程启动开始
+            let process_start = (stats.start_time as u128 * 1_000_000_000).to_string();
+            let ebpf_start = match status.whitelisted_at {
+                0 => process_start.clone(),
+                at => (at as u128 * 1_000_000_000).to_string(),
+            };
+            let now = now_nanos.to_string();
+
+            let point = |start: &str, value: Value, attributes: Vec<Value>| {
+                let mut point = json!({
+                    "startTimeUnixNano": start,
+                    "timeUnixNano": now,
+                    "attributes": attributes,
+                });
+                let key = if value.is_string() { "asInt" } else { "asDouble" };
+                point[key] = value;
+                point
+            };
+            let sum = |name: &str, unit: &str, description: &str, monotonic: bool, points: Vec<Value>| {
+                json!({
+                    "name": name,
+                    "unit": unit,
+                    "description": description,
+                    "sum": {
+                        "aggregationTemporality": CUMULATIVE,
+                        "isMonotonic": monotonic,
+                        "dataPoints": points,
+                    },
+                })
+            };
+            let int = |value: u64| Value::String(value.to_string());
+
+            let metrics = vec![
+                sum("process.cpu.time", "s", "Total CPU seconds", true, vec![
+                    point(&process_start, json!(stats.cpu_time_seconds), vec![]),
+                ]),
+                sum("process.memory.usage", "By", "The amount of physical memory in use", false, vec![
+                    point(&process_start, int(stats.memory_bytes), vec![]),
+                ]),
+                sum("process.memory.virtual", "By", "The amount of committed virtual memory", false, vec![
+                    point(&process_start, int(stats.virtual_memory_bytes), vec![]),
+                ]),
+                sum("process.disk.io", "By", "Disk bytes transferred", true, vec![
+                    point(&process_start, int(stats.disk_read_bytes), vec![attribute("disk.io.direction", "read")]),
+                    point(&process_start, int(stats.disk_written_bytes), vec![attribute("disk.io.direction", "write")]),
+                ]),
+                sum("process.network.io", "By", "Network bytes transferred", true, vec![
+                    point(&ebpf_start, int(stats.network_rx_bytes), vec![attribute("network.io.direction", "receive")]),
+                    point(&ebpf_start, int(stats.network_tx_bytes), vec![attribute("network.io.direction", "transmit")]),
+                ]),
+            ];
+
+            json!({
+                "resource": { "attributes": resource_attributes(status, hostname) },
+                "scopeMetrics": [{
+                    "scope": { "name": "process-exporter", "version": env!("CARGO_PKG_VERSION") },
+                    "metrics": metrics,
+                }],
+            })
+        })
+        .collect();
+
+    json!({ "resourceMetrics": resource_metrics })
+}
+
+/// 资源属性：主机、进程信息与注册标签（注册标签不覆盖内置属性）
+fn resource_attributes(status: &ProcessStatus, hostname: &str) -> Vec<Value> {
+    let mut attributes = vec![
+        ("service.name".to_string(), json!(status.config.name)),
+        ("host.name".to_string(), json!(hostname)),
+    ];
+    if let Some(pid) = status.pid {
+        attributes.push(("process.pid".to_string(), json!(pid.to_string())));
+    }
+    for (key, value) in [
+        ("process.executable.path", &status.stats.exe),
+        ("process.owner", &status.stats.user),
+        ("container.id", &status.stats.container_id),
+    ] {
+        if !value.is_empty() {
+            attributes.push((key.to_string(), json!(value)));
+        }
+    }
+
+    let mut labels: Vec<(&String, &String)> = status.config.labels.iter().collect();
+    labels.sort();
+    for (key, value) in labels {
+        if !attributes.iter().any(|(k, _)| k == key) {
+            attributes.push((key.clone(), json!(value)));
+        }
+    }
+
+    attributes.into_iter()
+        .map(|(key, value)| {
+            // process.pid 为 int 类型，其余为 string
+            let typed = if key == "process.pid" { json!({ "intValue": value }) } else { json!({ "stringValue": value }) };
+            json!({ "key": key, "value": typed })
+        })
+        .collect()
+}
+
+fn attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProcessConfig, ProcessStats};
+
+    #[test]
+    fn test_build_request() {
+        let mut config = ProcessConfig::new("nginx", "nginx: master");
+        config.labels.insert("team".to_string(), "web".to_string());
+        let mut status = ProcessStatus::new(config);
+        status.pid = Some(42);
+        status.whitelisted_at = 1_700_000_030;
+        status.stats = ProcessStats {
+            cpu_time_seconds: 1.5,
+            memory_bytes: 4096,
+            disk_read_bytes: 10,
+            disk_written_bytes: 20,
+            start_time: 1_700_000_000,
+            ..ProcessStats::default()
+        };
+
+        let request = build_request([&status], "host-a", 1_700_000_060_000_000_000);
+        let resource = &request["resourceMetrics"][0];
+
+        let attributes = resource["resource"]["attributes"].as_array().unwrap();
+        assert!(attributes.contains(&json!({ "key": "host.name", "value": { "stringValue": "host-a" } })));
+        assert!(attributes.contains(&json!({ "key": "process.pid", "value": { "intValue": "42" } })));
+        assert!(attributes.contains(&json!({ "key": "team", "value": { "stringValue": "web" } })));
+
+        let metrics = resource["scopeMetrics"][0]["metrics"].as_array().unwrap();
+        let metric = |name: &str| metrics.iter().find(|m| m["name"] == name).unwrap();
+
+        assert_eq!(metric("process.cpu.time")["sum"]["dataPoints"][0]["asDouble"], json!(1.5));
+        assert_eq!(metric("process.memory.usage")["sum"]["isMonotonic"], json!(false));
+
+        let disk = &metric("process.disk.io")["sum"]["dataPoints"][1];
+        assert_eq!(disk["asInt"], json!("20"));
+        assert_eq!(disk["attributes"][0]["value"]["stringValue"], json!("write"));
+        assert_eq!(disk["startTimeUnixNano"], json!("1700000000000000000"));
+        let network = &metric("process.network.io")["sum"]["dataPoints"][0];
+        assert_eq!(network["startTimeUnixNano"], json!("1700000030000000000"));
+    }
+}
@@ -0,0 +1,115 @@
+//! sink 传输方式：TCP、UDP、HTTP
+
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+
+/// 单个 UDP 数据报的最大负载，按行切分避免超过常见 MTU
+const MAX_DATAGRAM: usize = 1400;
+/// 连接与请求超时
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 传输方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transport {
+    /// tcp://host:port，每次写出建立新连接
+    Tcp(String),
+    /// udp://host:port
+    Udp(String),
+    /// http(s)://...，以 POST 发送
+    Http(String),
+}
+
+impl FromStr for Transport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (scheme, rest) = s.split_once("://")
+            .ok_or_else(|| format!("expected <scheme>://<address>, got '{}'", s))?;
+        if rest.is_empty() {
+            return Err(format!("missing address in '{}'", s));
+        }
+
+        match scheme.to_ascii_lowercase().as_str() {
+            "tcp" => Ok(Transport::Tcp(rest.to_string())),
+            "udp" => Ok(Transport::Udp(rest.to_string())),
+            "http" | "https" => Ok(Transport::Http(s.to_string())),
+            other => Err(format!("unsupported transport '{}', expected tcp, udp, http or https", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for Transport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Transport::Tcp(address) => write!(f, "tcp://{}", address),
+            Transport::Udp(address) => write!(f, "udp://{}", address),
+            Transport::Http(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+impl Transport {
+    /// 发送以换行分隔的文本负载
+    pub async fn send(&self, client: &reqwest::Client, payload: &str) -> anyhow::Result<()> {
+        if payload.is_empty() {
+            return Ok(());
+        }
+
+        match self {
+            Transport::Tcp(address) => {
+                let mut stream = tokio::time::timeout(TIMEOUT, TcpStream::connect(address))
+                    .await
+                    .with_context(|| format!("connect {} timed out", address))?
+                    .with_context(|| format!("connect {} failed", address))?;
+                stream.write_all(payload.as_bytes()).await?;
+                stream.shutdown().await?;
+            }
+            Transport::Udp(address) => {
+                let socket = UdpSocket::bind("0.0.0.0:0").await?;
+                socket.connect(address)
+                    .await
+                    .with_context(|| format!("resolve {} failed", address))?;
+                for datagram in datagrams(payload) {
+                    socket.send(datagram.as_bytes()).await?;
+                }
+            }
+            Transport::Http(url) => {
+                let response = client.post(url)
+                    .timeout(TIMEOUT)
+                    .header(reqwest::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                    .body(payload.to_string())
+                    .send()
+                    .await
+                    .with_context(|| format!("POST {} failed", url))?;
+
+                let status = response.status();
+                if !status.is_success() {
+                    let body = response.text().await.unwrap_or_default();
+                    bail!("{} returned {} {}", url, status, body.trim());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 按行打包为不超过 MAX_DATAGRAM 的数据报（单行超长时单独发送）
+fn datagrams(payload: &str) -> Vec<String> {
+    let mut datagrams = Vec::new();
+    let mut current = String::new();
+    for line in payload.lines() {
+        if !current.is_empty() && current.len() + line.len() + 1 > MAX_DATAGRAM {
+            datagrams.push(std::mem::take(&mut current));
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+    if !current.is_empty() {
+        datagrams.push(current);
+    }
+    datagrams
+}
@@ -0,0 +1,247 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+
+use crate::models::ListeningPort;
+
+/// TCP 状态名称，下标 + 1 即内核中的状态码（include/net/tcp_states.h）
+pub const TCP_STATES: [&str; 12] = [
+    "ESTABLISHED",
+    "SYN_SENT",
+    "SYN_RECV",
+    "FIN_WAIT1",
+    "FIN_WAIT2",
+    "TIME_WAIT",
+    "CLOSE",
+    "CLOSE_WAIT",
+    "LAST_ACK",
+    "LISTEN",
+    "CLOSING",
+    "NEW_SYN_RECV",
+];
+
+const TCP_TIME_WAIT: u8 = 0x06;
+const TCP_CLOSE: u8 = 0x07;
+const TCP_LISTEN: u8 = 0x0A;
+
+/// TCP 连接的本地与远端地址（/proc/net 中的十六进制原文，如 0100007F:1F90）
+pub type ConnectionKey = (String, String);
+
+/// /proc/<pid>/net/{tcp,udp}[6] 中的一条套接字记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct SocketEntry {
+    /// 本地地址与端口
+    pub local_address: String,
+    /// 远端地址与端口
+    pub remote_address: String,
+    /// 本地端口
+    pub local_port: u16,
+    /// 内核状态码
+    pub state: u8,
+    /// 套接字 inode
+    pub inode: u64,
+}
+
+/// 进程的套接字清单
+#[derive(Debug, Clone, Default)]
+pub struct SocketInventory {
+    /// 各 TCP 状态的连接数
+    pub tcp_connections: BTreeMap<String, u64>,
+    /// 监听中的端口
+    pub listening_ports: Vec<ListeningPort>,
+    /// 本进程的 TCP 连接（含已归属的 TIME_WAIT），下次采集时用于归属 TIME_WAIT
+    pub connections: HashSet<ConnectionKey>,
+}
+
+/// 将内核状态码转换为状态名
+pub fn tcp_state_name(state: u8) -> Option<&'static str> {
+    TCP_STATES.get(state.checked_sub(1)? as usize).copied()
+}
+
+/// 解析 /proc/net/tcp 格式的内容（tcp、tcp6、udp、udp6 格式相同）
+pub fn parse_proc_net(content: &str) -> Vec<SocketEntry> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 {
+                return None;
+            }
+
+            let local_port = u16::from_str_radix(fields[1].rsplit(':').next()?, 16).ok()?;
+            let state = u8::from_str_radix(fields[3], 16).ok()?;
+            let inode = fields[9].parse().ok()?;
+
+            Some(SocketEntry {
+                local_address: fields[1].to_string(),
+                remote_address: fields[2].to_string(),
+                local_port,
+                state,
+                inode,
+            })
+        })
+        .collect()
+}
+
+/// 读取 /proc/<pid>/fd 中所有套接字的 inode
+fn socket_inodes(pid: i32) -> HashSet<u64> {
+    let Ok(entries) = fs::read_dir(format!("/proc/{}/fd", pid)) else {
+        return HashSet::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| fs::read_link(entry.path()).ok())
+        .filter_map(|target| {
+            let target = target.to_string_lossy();
+            target.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+        })
+        .collect()
+}
+
+/// 统计属于本进程的 TIME_WAIT 套接字，并将其加入 `connections` 以便后续采集继续归属
+fn attribute_time_wait(
+    time_wait: Vec<SocketEntry>,
+    listen_ports: &HashSet<u16>,
+    known: &HashSet<ConnectionKey>,
+    connections: &mut HashSet<ConnectionKey>,
+) -> u64 {
+    let mut count = 0;
+    for entry in time_wait {
+        let key = (entry.local_address, entry.remote_address);
+        if listen_ports.contains(&entry.local_port) || known.contains(&key) {
+            count += 1;
+            connections.insert(key);
+        }
+    }
+    count
+}
+
+/// 收集进程的套接字清单
+///
+/// 通过 /proc/<pid>/fd 中的套接字 inode 匹配 /proc/<pid>/net 下的表项。
+/// TIME_WAIT 套接字已与进程脱离（inode 为 0），按本进程的 TCP 监听端口（被动关闭），
+/// 或上次采集时属于本进程的连接 `known`（主动关闭）归属；两次采集之间建立并关闭的连接无法归属。
+pub fn collect_socket_inventory(pid: i32, known: &HashSet<ConnectionKey>) -> SocketInventory {
+    let mut inventory = SocketInventory::default();
+    for state in TCP_STATES {
+        inventory.tcp_connections.insert(state.to_string(), 0);
+    }
+
+    let inodes = socket_inodes(pid);
+    if inodes.is_empty() && known.is_empty() {
+        return inventory;
+    }
+
+    let mut time_wait = Vec::new();
+
+    for (file, protocol) in [("tcp", "tcp"), ("tcp6", "tcp"), ("udp", "udp"), ("udp6", "udp")] {
+        let Ok(content) = fs::read_to_string(format!("/proc/{}/net/{}", pid, file)) else {
+            continue;
+        };
+
+        for entry in parse_proc_net(&content) {
+            if protocol == "tcp" && entry.state == TCP_TIME_WAIT {
+                time_wait.push(entry);
+                continue;
+            }
+
+            if !inodes.contains(&entry.inode) {
+                continue;
+            }
+
+            if protocol == "tcp" {
+                if let Some(name) = tcp_state_name(entry.state) {
+                    *inventory.tcp_connections.entry(name.to_string()).or_default() += 1;
+                }
+                if entry.state == TCP_LISTEN {
+                    inventory.listening_ports.push(ListeningPort {
+                        protocol: protocol.to_string(),
+                        port: entry.local_port,
+                    });
+                } else {
+                    inventory.connections.insert((entry.local_address, entry.remote_address));
+                }
+            } else if entry.state == TCP_CLOSE && entry.local_port != 0 {
+                // 未连接但已绑定的 UDP 套接字视为监听
+                inventory.listening_ports.push(ListeningPort {
+                    protocol: protocol.to_string(),
+                    port: entry.local_port,
+                });
+            }
+        }
+    }
+
+    let tcp_listen_ports: HashSet<u16> = inventory
+        .listening_ports
+        .iter()
+        .filter(|p| p.protocol == "tcp")
+        .map(|p| p.port)
+        .collect();
+    let time_wait = attribute_time_wait(time_wait, &tcp_listen_ports, known, &mut inventory.connections);
+    inventory.tcp_connections.insert("TIME_WAIT".to_string(), time_wait);
+
+    // 同一端口可能同时监听 IPv4 和 IPv6
+    inventory.listening_ports.sort();
+    inventory.listening_ports.dedup();
+
+    inventory
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROC_NET_TCP: &str = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41234 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:1F90 0100007F:D2F0 01 00000000:00000000 00:00000000 00000000  1000        0 41301 1 0000000000000000 20 4 30 10 -1
+   2: 0100007F:1F90 0100007F:D2EE 06 00000000:00000000 03:00000D2A 00000000     0        0 0 3 0000000000000000
+";
+
+    #[test]
+    fn test_parse_proc_net() {
+        let entries = parse_proc_net(PROC_NET_TCP);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].local_port, 8080);
+        assert_eq!(entries[0].state, TCP_LISTEN);
+        assert_eq!(entries[0].inode, 41234);
+        assert_eq!(entries[1].state, 0x01);
+        assert_eq!(entries[2].state, TCP_TIME_WAIT);
+        assert_eq!(entries[2].inode, 0);
+        assert_eq!(
+            (entries[2].local_address.as_str(), entries[2].remote_address.as_str()),
+            ("0100007F:1F90", "0100007F:D2EE")
+        );
+    }
+
+    #[test]
+    fn test_attribute_time_wait() {
+        let time_wait = |local: &str, remote: &str| {
+            parse_proc_net(&format!(
+                "header\n 0: {} {} 06 00000000:00000000 03:00000D2A 00000000 0 0 0 3 0000000000000000\n",
+                local, remote
+            ))
+        };
+        let mut entries = time_wait("0100007F:1F90", "0100007F:D2EE");
+        entries.extend(time_wait("0A000001:C350", "0A000002:1538"));
+        entries.extend(time_wait("0A000001:C351", "0A000003:01BB"));
+
+        let listen_ports = HashSet::from([8080]);
+        let known = HashSet::from([("0A000001:C350".to_string(), "0A000002:1538".to_string())]);
+        let mut connections = HashSet::new();
+
+        // 监听端口上的被动关闭与上次见过的主动连接，未见过的连接不归属
+        assert_eq!(attribute_time_wait(entries, &listen_ports, &known, &mut connections), 2);
+        assert!(connections.contains(&known.iter().next().unwrap().clone()));
+        assert_eq!(connections.len(), 2);
+    }
+
+    #[test]
+    fn test_tcp_state_name() {
+        assert_eq!(tcp_state_name(0x01), Some("ESTABLISHED"));
+        assert_eq!(tcp_state_name(0x0A), Some("LISTEN"));
+        assert_eq!(tcp_state_name(0x00), None);
+        assert_eq!(tcp_state_name(0x0D), None);
+    }
+}
@@ -1,22 +1,78 @@
-use crate::models::ProcessStats;
-use crate::services::ebpf_loader::EbpfLoader;
-use sysinfo::{System, Pid, ProcessesToUpdate};
+use crate::models::{LatencyHistogram, ProcessStats, ProtocolTraffic, TcpConnectStats};
+use crate::services::ebpf_loader::{EbpfLoader, Log2Histogram};
+use crate::services::{collect_socket_inventory, ConnectionKey};
+use sysinfo::{System, Pid, ProcessesToUpdate, Uid, Users};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 
 pub struct StatsCollector {
     system: Mutex<System>,
+    users: Mutex<Users>,
     ebpf_loader: Arc<EbpfLoader>,  // ← 添加 eBPF loader 引用
+    /// 上次采集时各进程的 TCP 连接，用于归属主动关闭后进入 TIME_WAIT 的连接
+    connections: Mutex<HashMap<i32, HashSet<ConnectionKey>>>,
 }
 
 impl StatsCollector {
     pub fn new(ebpf_loader: Arc<EbpfLoader>) -> Self {  // ← 接收 eBPF loader
         Self {
             system: Mutex::new(System::new_all()),
+            users: Mutex::new(Users::new_with_refreshed_list()),
             ebpf_loader,
+            connections: Mutex::new(HashMap::new()),
         }
     }
 
-    pub async fn collect_stats(&self, pid: i32) -> Option<ProcessStats> {  // ← 改为 async
+    /// 进程已退出或不再监控时清理其连接记录
+    pub fn forget(&self, pid: i32) {
+        if let Ok(mut connections) = self.connections.lock() {
+            connections.remove(&pid);
+        }
+    }
+
+    /// 采集进程统计，只运行 `active` 中采集器需要的工作
+    ///
+    /// 未运行的采集器对应字段沿用 `base`（同一 PID 上次的统计），避免按采集器过滤的抓取清空其他字段
+    pub async fn collect_stats(&self, pid: i32, base: ProcessStats, active: &HashSet<&str>) -> Option<ProcessStats> {  // ← 改为 async
+        let mut stats = base;
+
+        // *** 从 eBPF 读取网络、TCP、调度统计 ***（在获取 System 锁之前完成，避免跨 await 持锁）
+        if active.contains("network") {
+            stats.network_by_protocol = self.ebpf_loader
+                .get_network_stats(pid)
+                .await
+                .into_iter()
+                .map(|(protocol, stats)| (protocol.to_string(), ProtocolTraffic::from(stats)))
+                .collect();
+            let network = stats.network_by_protocol.values().fold(ProtocolTraffic::default(), |total, t| ProtocolTraffic {
+                tx_bytes: total.tx_bytes + t.tx_bytes,
+                rx_bytes: total.rx_bytes + t.rx_bytes,
+                tx_packets: total.tx_packets + t.tx_packets,
+                rx_packets: total.rx_packets + t.rx_packets,
+            });
+            stats.network_rx_bytes = network.rx_bytes;
+            stats.network_tx_bytes = network.tx_bytes;
+            stats.network_rx_packets = network.rx_packets;
+            stats.network_tx_packets = network.tx_packets;
+        }
+
+        if active.contains("tcp") {
+            let tcp = self.ebpf_loader.get_tcp_stats(pid).await;
+            stats.tcp_retransmits = tcp.retransmits;
+            stats.tcp_rtt = tcp.rtt.map(LatencyHistogram::from);
+            stats.tcp_connects = tcp.connects.map(TcpConnectStats::from).unwrap_or_default();
+        }
+
+        if active.contains("cpu") {
+            let sched = self.ebpf_loader.get_sched_stats(pid).await;
+            let sched_histogram = |histogram: Option<_>| {
+                histogram.filter(|h: &Log2Histogram| h.count > 0).map(LatencyHistogram::from)
+            };
+            stats.off_cpu = sched_histogram(sched.map(|s| s.off_cpu));
+            stats.run_queue_latency = sched_histogram(sched.map(|s| s.run_queue));
+        }
+
         let mut sys = self.system.lock().ok()?;
 
         let sysinfo_pid = Pid::from_u32(pid as u32);
@@ -25,32 +81,64 @@ impl StatsCollector {
         let process = sys.process(sysinfo_pid)?;
         let total_memory = sys.total_memory();
 
-        // *** 从 eBPF 读取网络统计 ***
-        let network_stats = self.ebpf_loader.get_network_stats(pid).await;
-        let (rx_bytes, tx_bytes, rx_packets, tx_packets) = if let Some(stats) = network_stats {
-            (stats.rx_bytes, stats.tx_bytes, stats.rx_packets, stats.tx_packets)
+        // 进程表字段在刷新后即可读取，始终更新（也用于判断统计是否有效）
+        stats.cpu_usage = process.cpu_usage();
+        stats.cpu_time_seconds = process.accumulated_cpu_time() as f64 / 1000.0;
+        stats.memory_bytes = process.memory();
+        stats.memory_percent = if total_memory > 0 {
+            (process.memory() as f32 / total_memory as f32) * 100.0
         } else {
-            (0, 0, 0, 0)
+            0.0
         };
+        stats.virtual_memory_bytes = process.virtual_memory();
+        stats.disk_read_bytes = process.disk_usage().total_read_bytes;
+        stats.disk_written_bytes = process.disk_usage().total_written_bytes;
+        stats.start_time = process.start_time();
 
-        let stats = ProcessStats {
-            cpu_usage: process.cpu_usage(),
-            memory_bytes: process.memory(),
-            memory_percent: if total_memory > 0 {
-                (process.memory() as f32 / total_memory as f32) * 100.0
-            } else {
-                0.0
-            },
-            virtual_memory_bytes: process.virtual_memory(),
-            disk_read_bytes: process.disk_usage().read_bytes,
-            disk_written_bytes: process.disk_usage().written_bytes,
-            thread_count: 0,
-            network_rx_bytes: rx_bytes,
-            network_tx_bytes: tx_bytes,
-            network_rx_packets: rx_packets,
-            network_tx_packets: tx_packets,
-        };
+        // 套接字清单（TCP 连接状态、监听端口）
+        if active.contains("sockets") {
+            let mut connections = self.connections.lock().ok()?;
+            let known = connections.remove(&pid).unwrap_or_default();
+            let sockets = collect_socket_inventory(pid, &known);
+            connections.insert(pid, sockets.connections);
+            drop(connections);
+
+            stats.tcp_connections = sockets.tcp_connections;
+            stats.listening_ports = sockets.listening_ports;
+        }
+
+        if active.contains("process") {
+            stats.user = process.user_id().map(|uid| self.user_name(uid)).unwrap_or_default();
+            stats.exe = process.exe().map(|p| p.display().to_string()).unwrap_or_default();
+            stats.container_id = container_id(pid).unwrap_or_default();
+        }
 
         Some(stats)
     }
+
+    /// 将 UID 解析为用户名，找不到时刷新一次用户列表，仍失败则返回数字 UID
+    fn user_name(&self, uid: &Uid) -> String {
+        let Ok(mut users) = self.users.lock() else {
+            return uid.to_string();
+        };
+
+        if users.get_user_by_id(uid).is_none() {
+            users.refresh();
+        }
+
+        users.get_user_by_id(uid)
+            .map(|user| user.name().to_string())
+            .unwrap_or_else(|| uid.to_string())
+    }
+}
+
+/// 从 /proc/<pid>/cgroup 中提取容器 ID（docker、containerd、cri-o、podman 均使用 64 位十六进制 ID）
+fn container_id(pid: i32) -> Option<String> {
+    let content = std::fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    let regex = Regex::new(r"[0-9a-f]{64}").ok()?;
+
+    content.lines()
+        .rev()
+        .find_map(|line| regex.find_iter(line).last())
+        .map(|m| m.as_str().to_string())
 }
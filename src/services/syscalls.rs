@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use crate::models::SyscallUsage;
+use crate::services::ebpf_loader::{SyscallKey, SyscallStats};
+
+/// x86_64 系统调用名，下标即调用号（0 ~ 334）
+#[cfg(target_arch = "x86_64")]
+const ARCH_SYSCALLS: &[&str] = &[
+    "read", "write", "open", "close", "stat", "fstat", "lstat", "poll", "lseek", "mmap",
+    "mprotect", "munmap", "brk", "rt_sigaction", "rt_sigprocmask", "rt_sigreturn", "ioctl", "pread64", "pwrite64", "readv",
+    "writev", "access", "pipe", "select", "sched_yield", "mremap", "msync", "mincore", "madvise", "shmget",
+    "shmat", "shmctl", "dup", "dup2", "pause", "nanosleep", "getitimer", "alarm", "setitimer", "getpid",
+    "sendfile", "socket", "connect", "accept", "sendto", "recvfrom", "sendmsg", "recvmsg", "shutdown", "bind",
+    "listen", "getsockname", "getpeername", "socketpair", "setsockopt", "getsockopt", "clone", "fork", "vfork", "execve",
+    "exit", "wait4", "kill", "uname", "semget", "semop", "semctl", "shmdt", "msgget", "msgsnd",
+    "msgrcv", "msgctl", "fcntl", "flock", "fsync", "fdatasync", "truncate", "ftruncate", "getdents", "getcwd",
+    "chdir", "fchdir", "rename", "mkdir", "rmdir", "creat", "link", "unlink", "symlink", "readlink",
+    "chmod", "fchmod", "chown", "fchown", "lchown", "umask", "gettimeofday", "getrlimit", "getrusage", "sysinfo",
+    "times", "ptrace", "getuid", "syslog", "getgid", "setuid", "setgid", "geteuid", "getegid", "setpgid",
+    "getppid", "getpgrp", "setsid", "setreuid", "setregid", "getgroups", "setgroups", "setresuid", "getresuid", "setresgid",
+    "getresgid", "getpgid", "setfsuid", "setfsgid", "getsid", "capget", "capset", "rt_sigpending", "rt_sigtimedwait", "rt_sigqueueinfo",
+    "rt_sigsuspend", "sigaltstack", "utime", "mknod", "uselib", "personality", "ustat", "statfs", "fstatfs", "sysfs",
+    "getpriority", "setpriority", "sched_setparam", "sched_getparam", "sched_setscheduler", "sched_getscheduler", "sched_get_priority_max", "sched_get_priority_min", "sched_rr_get_interval", "mlock",
+    "munlock", "mlockall", "munlockall", "vhangup", "modify_ldt", "pivot_root", "_sysctl", "prctl", "arch_prctl", "adjtimex",
+    "setrlimit", "chroot", "sync", "acct", "settimeofday", "mount", "umount2", "swapon", "swapoff", "reboot",
+    "sethostname", "setdomainname", "iopl", "ioperm", "create_module", "init_module", "delete_module", "get_kernel_syms", "query_module", "quotactl",
+    "nfsservctl", "getpmsg", "putpmsg", "afs_syscall", "tuxcall", "security", "gettid", "readahead", "setxattr", "lsetxattr",
+    "fsetxattr", "getxattr", "lgetxattr", "fgetxattr", "listxattr", "llistxattr", "flistxattr", "removexattr", "lremovexattr", "fremovexattr",
+    "tkill", "time", "futex", "sched_setaffinity", "sched_getaffinity", "set_thread_area", "io_setup", "io_destroy", "io_getevents", "io_submit",
+    "io_cancel", "get_thread_area", "lookup_dcookie", "epoll_create", "epoll_ctl_old", "epoll_wait_old", "remap_file_pages", "getdents64", "set_tid_address", "restart_syscall",
+    "semtimedop", "fadvise64", "timer_create", "timer_settime", "timer_gettime", "timer_getoverrun", "timer_delete", "clock_settime", "clock_gettime", "clock_getres",
+    "clock_nanosleep", "exit_group", "epoll_wait", "epoll_ctl", "tgkill", "utimes", "vserver", "mbind", "set_mempolicy", "get_mempolicy",
+    "mq_open", "mq_unlink", "mq_timedsend", "mq_timedreceive", "mq_notify", "mq_getsetattr", "kexec_load", "waitid", "add_key", "request_key",
+    "keyctl", "ioprio_set", "ioprio_get", "inotify_init", "inotify_add_watch", "inotify_rm_watch", "migrate_pages", "openat", "mkdirat", "mknodat",
+    "fchownat", "futimesat", "newfstatat", "unlinkat", "renameat", "linkat", "symlinkat", "readlinkat", "fchmodat", "faccessat",
+    "pselect6", "ppoll", "unshare", "set_robust_list", "get_robust_list", "splice", "tee", "sync_file_range", "vmsplice", "move_pages",
+    "utimensat", "epoll_pwait", "signalfd", "timerfd_create", "eventfd", "fallocate", "timerfd_settime", "timerfd_gettime", "accept4", "signalfd4",
+    "eventfd2", "epoll_create1", "dup3", "pipe2", "inotify_init1", "preadv", "pwritev", "rt_tgsigqueueinfo", "perf_event_open", "recvmmsg",
+    "fanotify_init", "fanotify_mark", "prlimit64", "name_to_handle_at", "open_by_handle_at", "clock_adjtime", "syncfs", "sendmmsg", "setns", "getcpu",
+    "process_vm_readv", "process_vm_writev", "kcmp", "finit_module", "sched_setattr", "sched_getattr", "renameat2", "seccomp", "getrandom", "memfd_create",
+    "kexec_file_load", "bpf", "execveat", "userfaultfd", "membarrier", "mlock2", "copy_file_range", "preadv2", "pwritev2", "pkey_mprotect",
+    "pkey_alloc", "pkey_free", "statx", "io_pgetevents", "rseq",
+];
+
+/// 其他架构暂无调用号表，只能按 424 起的通用调用号解析
+#[cfg(not(target_arch = "x86_64"))]
+const ARCH_SYSCALLS: &[&str] = &[];
+
+/// 自 424 起各架构统一的系统调用号
+const GENERIC_SYSCALLS_START: u32 = 424;
+
+const GENERIC_SYSCALLS: &[&str] = &[
+    "pidfd_send_signal", "io_uring_setup", "io_uring_enter", "io_uring_register", "open_tree", "move_mount", "fsopen", "fsconfig", "fsmount", "fspick",
+    "pidfd_open", "clone3", "close_range", "openat2", "pidfd_getfd", "faccessat2", "process_madvise", "epoll_pwait2", "mount_setattr", "quotactl_fd",
+    "landlock_create_ruleset", "landlock_add_rule", "landlock_restrict_self", "memfd_secret", "process_mrelease", "futex_waitv", "set_mempolicy_home_node", "cachestat", "fchmodat2", "map_shadow_stack",
+    "futex_wake", "futex_wait", "futex_requeue",
+];
+
+/// 系统调用号对应的名称
+pub fn syscall_name(nr: u32) -> Option<&'static str> {
+    match nr.checked_sub(GENERIC_SYSCALLS_START) {
+        Some(offset) => GENERIC_SYSCALLS.get(offset as usize).copied(),
+        None => ARCH_SYSCALLS.get(nr as usize).copied(),
+    }
+}
+
+/// 将 eBPF 的系统调用条目按 PID 分组，每个进程按调用次数降序排列
+pub fn syscalls_by_pid<I>(raw: I) -> HashMap<u32, Vec<SyscallUsage>>
+where
+    I: IntoIterator<Item = (SyscallKey, SyscallStats)>,
+{
+    let mut grouped: HashMap<u32, Vec<SyscallUsage>> = HashMap::new();
+    for (key, stats) in raw {
+        // 只有退出事件（计数前已挂载的调用）时没有调用次数
+        if stats.count == 0 {
+            continue;
+        }
+        grouped.entry(key.tgid).or_default().push(SyscallUsage {
+            nr: key.nr,
+            name: syscall_name(key.nr)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("syscall_{}", key.nr)),
+            count: stats.count,
+            errors: stats.errors,
+        });
+    }
+
+    for syscalls in grouped.values_mut() {
+        syscalls.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.nr.cmp(&b.nr)));
+    }
+    grouped
+}
+
+/// 出过错的系统调用按错误率降序排列，错误率相同时错误次数多的在前
+pub fn sort_by_error_rate(syscalls: &[SyscallUsage]) -> Vec<&SyscallUsage> {
+    let mut failing: Vec<&SyscallUsage> = syscalls.iter().filter(|s| s.errors > 0).collect();
+    failing.sort_by(|a, b| {
+        b.error_rate().total_cmp(&a.error_rate())
+            .then_with(|| b.errors.cmp(&a.errors))
+            .then_with(|| a.nr.cmp(&b.nr))
+    });
+    failing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(tgid: u32, nr: u32, count: u64, errors: u64) -> (SyscallKey, SyscallStats) {
+        (SyscallKey { tgid, nr }, SyscallStats { count, errors })
+    }
+
+    #[test]
+    fn test_syscalls_by_pid() {
+        let raw = vec![
+            entry(1, 0, 100, 0),
+            entry(1, 1, 300, 3),
+            entry(1, 2, 10, 5),
+            entry(1, 3, 0, 1),
+            entry(2, 9999, 1, 1),
+        ];
+
+        let grouped = syscalls_by_pid(raw);
+        let by_count: Vec<u32> = grouped[&1].iter().map(|s| s.nr).collect();
+        assert_eq!(by_count, vec![1, 0, 2]);
+        assert_eq!(grouped[&2][0].name, "syscall_9999");
+
+        let by_rate: Vec<u32> = sort_by_error_rate(&grouped[&1]).iter().map(|s| s.nr).collect();
+        assert_eq!(by_rate, vec![2, 1]);
+        assert_eq!(syscall_name(435), Some("clone3"));
+        #[cfg(target_arch = "x86_64")]
+        assert_eq!(syscall_name(257), Some("openat"));
+    }
+}
@@ -1,12 +1,26 @@
-use crate::models::ProcessStatus;
+use crate::models::{FlapPolicy, NamedProcessEvent, ProcessHistory, ProcessStatus};
 use crate::services::{StatsCollector, ebpf_loader::EbpfLoader};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// 归属后生命周期事件的广播容量
+const NAMED_EVENT_CAPACITY: usize = 1024;
 
 pub struct AppStateInner {
     pub processes: HashMap<String, ProcessStatus>,
-    pub stats_collector: StatsCollector,
+    /// 每个注册进程的历史采样
+    pub history: HashMap<String, ProcessHistory>,
+    /// 重启抖动判定
+    pub flap_policy: FlapPolicy,
+    /// 是否在进程独占的 cgroup 上挂载 cgroup_skb 统计
+    pub cgroup_network: bool,
+    /// 是否接受 exec 探测的注册
+    pub allow_exec_probes: bool,
+    pub stats_collector: Arc<StatsCollector>,
     pub ebpf_loader: Arc<EbpfLoader>,
+    /// 已归属到注册进程的生命周期事件，由后台任务统一归属后广播给 SSE 订阅者
+    pub events: broadcast::Sender<NamedProcessEvent>,
 }
 
 pub type AppState = Arc<Mutex<AppStateInner>>;
@@ -16,7 +30,12 @@ pub fn new_state() -> AppState {
     
     Arc::new(Mutex::new(AppStateInner {
         processes: HashMap::new(),
-        stats_collector: StatsCollector::new(ebpf_loader.clone()),  // ← 传递 ebpf_loader
+        history: HashMap::new(),
+        flap_policy: FlapPolicy::default(),
+        cgroup_network: false,
+        allow_exec_probes: false,
+        stats_collector: Arc::new(StatsCollector::new(ebpf_loader.clone())),  // ← 传递 ebpf_loader
         ebpf_loader,
+        events: broadcast::channel(NAMED_EVENT_CAPACITY).0,
     }))
 }